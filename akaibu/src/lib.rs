@@ -12,6 +12,7 @@ extern crate positioned_io_preview as positioned_io;
 pub mod archive;
 pub mod error;
 pub mod magic;
+pub mod options;
 pub mod resource;
 pub mod scheme;
 pub mod util;
@@ -4,6 +4,7 @@ use enum_iterator::IntoEnumIterator;
 #[derive(Debug, IntoEnumIterator)]
 pub enum Archive {
     ACV1,
+    CPZ6,
     CPZ7,
     GXP,
     PF8,
@@ -11,7 +12,13 @@ pub enum Archive {
     BURIKO,
     EscArc2,
     Malie,
+    MalieUnencrypted,
     Silky,
+    Siglus,
+    OVK,
+    NSA,
+    SAR,
+    XP3,
     NotRecognized,
 }
 
@@ -21,26 +28,47 @@ impl Archive {
         match buf {
             // ACV1
             [0x41, 0x43, 0x56, 0x31, ..] => Self::ACV1,
+            // CPZ5 or CPZ6
+            [0x43, 0x50, 0x5A, 0x35, ..] | [0x43, 0x50, 0x5A, 0x36, ..] => {
+                Self::CPZ6
+            }
             // CPZ7
             [0x43, 0x50, 0x5A, 0x37, ..] => Self::CPZ7,
             // GXP\x00
             [0x47, 0x58, 0x50, 0x00, ..] => Self::GXP,
-            // pf8
-            [0x70, 0x66, 0x38, ..] => Self::PF8,
+            // pf8, pf6 or pf2
+            [0x70, 0x66, 0x38, ..]
+            | [0x70, 0x66, 0x36, ..]
+            | [0x70, 0x66, 0x32, ..] => Self::PF8,
             // YFP\x00
             [0x59, 0x50, 0x46, 0x00, ..] => Self::YPF,
             // BURIKO ARC20
             [0x42, 0x55, 0x52, 0x49, 0x4b, 0x4f, 0x20, 0x41, 0x52, 0x43, 0x32, 0x30, ..] => {
                 Self::BURIKO
             }
-            // ESC-ARC2
-            [0x45, 0x53, 0x43, 0x2D, 0x41, 0x52, 0x43, 0x32, ..] => {
+            // PackFile
+            [0x50, 0x61, 0x63, 0x6B, 0x46, 0x69, 0x6C, 0x65, 0x20, 0x20, 0x20, 0x20, ..] => {
+                Self::BURIKO
+            }
+            // ESC-ARC1 or ESC-ARC2
+            [0x45, 0x53, 0x43, 0x2D, 0x41, 0x52, 0x43, 0x31, ..]
+            | [0x45, 0x53, 0x43, 0x2D, 0x41, 0x52, 0x43, 0x32, ..] => {
                 Self::EscArc2
             }
-            // No magic but each game has only one archive so we can just hardcode first 4 bytes here
-            [0xc1, 0xf2, 0x5e, 0x79, ..] | [0x7f, 0x4d, 0x8f, 0xe9, ..] => {
-                Self::Malie
+            // LIBP
+            [0x4C, 0x49, 0x42, 0x50, ..] => Self::MalieUnencrypted,
+            // Scene.pck has no magic, match header size and first list offset
+            [0x5C, 0x00, 0x00, 0x00, 0x5C, 0x00, 0x00, 0x00, ..] => {
+                Self::Siglus
+            }
+            // XP3\r\n \n\x1A\x8B\x67\x01
+            [0x58, 0x50, 0x33, 0x0D, 0x0A, 0x20, 0x0A, 0x1A, 0x8B, 0x67, 0x01, ..] => {
+                Self::XP3
             }
+            buf if scheme::malie::is_malie(buf) => Self::Malie,
+            buf if scheme::ovk::is_ovk(buf) => Self::OVK,
+            buf if scheme::nscripter::is_nsa(buf) => Self::NSA,
+            buf if scheme::nscripter::is_sar(buf) => Self::SAR,
             _ => Self::NotRecognized,
         }
     }
@@ -48,6 +76,7 @@ impl Archive {
     pub fn is_universal(&self) -> bool {
         match self {
             Self::ACV1 => false,
+            Self::CPZ6 => true,
             Self::CPZ7 => false,
             Self::GXP => true,
             Self::PF8 => true,
@@ -55,7 +84,13 @@ impl Archive {
             Self::BURIKO => true,
             Self::EscArc2 => true,
             Self::Malie => false,
+            Self::MalieUnencrypted => true,
             Self::Silky => true,
+            Self::Siglus => true,
+            Self::OVK => true,
+            Self::NSA => true,
+            Self::SAR => true,
+            Self::XP3 => false,
             Self::NotRecognized => false,
         }
     }
@@ -63,6 +98,7 @@ impl Archive {
     pub fn get_schemes(&self) -> Vec<Box<dyn Scheme>> {
         match self {
             Self::ACV1 => scheme::acv1::Acv1Scheme::get_schemes(),
+            Self::CPZ6 => scheme::cpz6::Cpz6Scheme::get_schemes(),
             Self::CPZ7 => scheme::cpz7::Cpz7Scheme::get_schemes(),
             Self::GXP => scheme::gxp::GxpScheme::get_schemes(),
             Self::PF8 => scheme::pf8::Pf8Scheme::get_schemes(),
@@ -70,7 +106,19 @@ impl Archive {
             Self::BURIKO => scheme::buriko::BurikoScheme::get_schemes(),
             Self::EscArc2 => scheme::esc_arc2::EscArc2Scheme::get_schemes(),
             Self::Malie => scheme::malie::MalieScheme::get_schemes(),
+            Self::MalieUnencrypted => {
+                vec![Box::new(scheme::malie::MalieScheme::Unencrypted)]
+            }
             Self::Silky => scheme::silky::SilkyScheme::get_schemes(),
+            Self::Siglus => scheme::siglus::SiglusScheme::get_schemes(),
+            Self::OVK => scheme::ovk::OvkScheme::get_schemes(),
+            Self::NSA => {
+                vec![Box::new(scheme::nscripter::NscripterScheme::Nsa)]
+            }
+            Self::SAR => {
+                vec![Box::new(scheme::nscripter::NscripterScheme::Sar)]
+            }
+            Self::XP3 => scheme::xp3::Xp3Scheme::get_schemes(),
             Self::NotRecognized => vec![],
         }
     }
@@ -0,0 +1,14 @@
+use crate::scheme::{
+    acv1::Acv1Options, buriko::BurikoOptions, malie::MalieOptions,
+    siglus::SiglusOptions,
+};
+
+/// User provided options, passed to schemes when extracting archives and to
+/// resource schemes when converting resources
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub acv1: Acv1Options,
+    pub siglus: SiglusOptions,
+    pub buriko: BurikoOptions,
+    pub malie: MalieOptions,
+}
@@ -0,0 +1,76 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{options::Options, util::find_sjis_strings};
+use encoding_rs::SHIFT_JIS;
+use std::{fs::File, io::Read, path::PathBuf};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Acv1ScriptScheme {
+    Universal,
+}
+
+impl ResourceScheme for Acv1ScriptScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 16);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[ACV1 SCRIPT TEXT] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl Acv1ScriptScheme {
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        Ok(ResourceType::Text(dump_script_text(&buf)))
+    }
+}
+
+/// Dump text of decompressed script without parsing its commands. Plain
+/// text scripts keep lines with non ASCII characters while comment and
+/// command lines are skipped, so dialogue, speaker names and choices are
+/// dumped alike. Compiled scripts are scanned for null terminated strings
+/// instead. Each line is prefixed with its line number or offset.
+pub(crate) fn dump_script_text(buf: &[u8]) -> String {
+    let (script, _, had_errors) = SHIFT_JIS.decode(buf);
+    let mut text = String::new();
+    if !had_errors && !buf.contains(&0) {
+        script
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.chars().all(|c| c.is_ascii()))
+            .filter(|(_, line)| !line.starts_with(&['#', ';', '/', '@'][..]))
+            .for_each(|(i, line)| {
+                text.push_str(&format!("{:06}\t{}\n", i + 1, line))
+            });
+        return text;
+    }
+    find_sjis_strings(buf)
+}
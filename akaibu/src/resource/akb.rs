@@ -1,5 +1,5 @@
 use super::{ResourceScheme, ResourceType};
-use crate::{error::AkaibuError, util::image::bitmap_to_png};
+use crate::{error::AkaibuError, options::Options, util::image::bitmap_to_png};
 use anyhow::Context;
 use image::{buffer::ConvertBuffer, ImageBuffer, Pixel};
 use scroll::Pread;
@@ -24,7 +24,11 @@ struct AkbHeader {
 }
 
 impl ResourceScheme for AkbScheme {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType> {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
         file.read_to_end(&mut buf)?;
@@ -35,6 +39,7 @@ impl ResourceScheme for AkbScheme {
         &self,
         _file_path: &PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         self.from_bytes(buf)
     }
@@ -0,0 +1,180 @@
+use super::{AudioCodec, ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf, time::Duration};
+
+const RIFF_MAGIC: &[u8] = b"RIFF";
+const WAVE_MAGIC: &[u8] = b"WAVE";
+const OGG_MAGIC: &[u8] = b"OggS";
+const BURIKO_SOUND_MAGIC: &[u8] = b"bw  ";
+const OGG_PAGE_HEADER_SIZE: usize = 27;
+/// Opus granule position is always counted at 48kHz
+const OPUS_GRANULE_RATE: u32 = 48000;
+
+#[derive(Debug, Clone)]
+pub(crate) enum AudioScheme {
+    Universal,
+}
+
+impl ResourceScheme for AudioScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[AUDIO] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl AudioScheme {
+    fn from_bytes(&self, mut buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        // BURIKO sounds are wrapped in header with its size as first field
+        if buf.get(4..8) == Some(BURIKO_SOUND_MAGIC) {
+            let header_size = buf.pread_with::<u32>(0, LE)? as usize;
+            if header_size > buf.len() {
+                return Err(AkaibuError::Custom(format!(
+                    "Invalid BURIKO sound header size: {}",
+                    header_size
+                ))
+                .into());
+            }
+            buf = buf.split_off(header_size);
+        }
+        if buf.starts_with(RIFF_MAGIC) && buf.get(8..12) == Some(WAVE_MAGIC) {
+            from_wav(buf)
+        } else if buf.starts_with(OGG_MAGIC) {
+            from_ogg(buf)
+        } else {
+            Err(AkaibuError::Unimplemented(
+                "Audio is neither RIFF/WAVE nor Ogg".to_owned(),
+            )
+            .into())
+        }
+    }
+}
+
+/// Read format and length of RIFF/WAVE file from its `fmt ` and `data`
+/// chunks
+pub(crate) fn from_wav(buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+    let mut format = None;
+    let mut data_size = 0;
+    let mut off = 12;
+    while off + 8 <= buf.len() {
+        let chunk_id = &buf[off..off + 4];
+        let chunk_size = buf.pread_with::<u32>(off + 4, LE)? as usize;
+        if chunk_id == b"fmt " {
+            let channels = buf.pread_with::<u16>(off + 10, LE)?;
+            let sample_rate = buf.pread_with::<u32>(off + 12, LE)?;
+            let byte_rate = buf.pread_with::<u32>(off + 16, LE)?;
+            format = Some((channels, sample_rate, byte_rate));
+        } else if chunk_id == b"data" {
+            data_size = chunk_size.min(buf.len() - off - 8);
+        }
+        // Chunks are padded to even size
+        off += 8 + chunk_size + (chunk_size & 1);
+    }
+    let (channels, sample_rate, byte_rate) =
+        format.context("WAVE file without fmt chunk")?;
+    let duration = if byte_rate != 0 {
+        Duration::from_secs_f64(data_size as f64 / byte_rate as f64)
+    } else {
+        Duration::default()
+    };
+    Ok(ResourceType::Audio {
+        codec: AudioCodec::Wav,
+        sample_rate,
+        channels,
+        duration,
+        data: buf,
+    })
+}
+
+/// Read format from identification header in first page and length from
+/// granule position of last page
+fn from_ogg(buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+    let segment_count = buf.pread::<u8>(OGG_PAGE_HEADER_SIZE - 1)? as usize;
+    let packet_start = OGG_PAGE_HEADER_SIZE + segment_count;
+    let packet = buf.get(packet_start..).context("Out of bounds access")?;
+    let last_page = buf
+        .windows(OGG_MAGIC.len())
+        .rposition(|w| w == OGG_MAGIC)
+        .context("Ogg page not found")?;
+    // Position of -1 means no packet finishes on the page
+    let granule_position = match buf.pread_with::<u64>(last_page + 6, LE)? {
+        u64::MAX => 0,
+        granule_position => granule_position,
+    };
+
+    let (codec, channels, sample_rate, samples, granule_rate) =
+        if packet.starts_with(b"\x01vorbis") {
+            let channels = packet.pread::<u8>(11)? as u16;
+            let sample_rate = packet.pread_with::<u32>(12, LE)?;
+            (
+                AudioCodec::Vorbis,
+                channels,
+                sample_rate,
+                granule_position,
+                sample_rate,
+            )
+        } else if packet.starts_with(b"OpusHead") {
+            let channels = packet.pread::<u8>(9)? as u16;
+            let pre_skip = packet.pread_with::<u16>(10, LE)? as u64;
+            let sample_rate = match packet.pread_with::<u32>(12, LE)? {
+                0 => OPUS_GRANULE_RATE,
+                sample_rate => sample_rate,
+            };
+            (
+                AudioCodec::Opus,
+                channels,
+                sample_rate,
+                granule_position.saturating_sub(pre_skip),
+                OPUS_GRANULE_RATE,
+            )
+        } else {
+            return Err(AkaibuError::Unimplemented(
+                "Ogg stream is neither Vorbis nor Opus".to_owned(),
+            )
+            .into());
+        };
+    let duration = if granule_rate != 0 {
+        Duration::from_secs_f64(samples as f64 / granule_rate as f64)
+    } else {
+        Duration::default()
+    };
+    Ok(ResourceType::Audio {
+        codec,
+        sample_rate,
+        channels,
+        duration,
+        data: buf,
+    })
+}
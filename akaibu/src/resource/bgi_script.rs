@@ -0,0 +1,109 @@
+use super::{ResourceScheme, ResourceType};
+use crate::options::Options;
+use encoding_rs::SHIFT_JIS;
+use scroll::{Pread, LE};
+use std::{collections::BTreeMap, fs::File, io::Read, path::PathBuf};
+
+const MAGIC: &[u8] = b"BurikoCompiledScriptVer1.00\0";
+/// Instruction pushing string address on stack
+const OPCODE_PUSH_STRING: u32 = 0x03;
+
+#[derive(Debug, Clone)]
+pub(crate) enum BgiScriptScheme {
+    Universal,
+}
+
+impl ResourceScheme for BgiScriptScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[BGI SCRIPT] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl BgiScriptScheme {
+    /// Dump strings referenced by bytecode, one per line prefixed with
+    /// string offset relative to start of bytecode
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        let code_start = if buf.starts_with(MAGIC) {
+            MAGIC.len() + buf.pread_with::<u32>(MAGIC.len(), LE)? as usize
+        } else {
+            0
+        };
+        let strings = Self::find_strings(&buf, code_start);
+        let text = strings
+            .iter()
+            .filter_map(|(offset, end)| {
+                let (text, _, _) =
+                    SHIFT_JIS.decode(&buf[code_start + offset..*end]);
+                if text.chars().any(|c| !c.is_ascii()) {
+                    Some(format!(
+                        "{:08X}\t{}\n",
+                        offset,
+                        text.replace("\n", "\\n")
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect::<String>();
+        Ok(ResourceType::Text(text))
+    }
+    /// Scan bytecode for string pushes. String section starts at lowest
+    /// referenced offset so scanning stops there.
+    fn find_strings(buf: &[u8], code_start: usize) -> BTreeMap<usize, usize> {
+        let mut strings = BTreeMap::new();
+        let mut text_start = buf.len();
+        let mut off = code_start;
+        while off + 8 <= text_start {
+            let opcode = buf.pread_with::<u32>(off, LE).unwrap_or_default();
+            off += 4;
+            if opcode != OPCODE_PUSH_STRING {
+                continue;
+            }
+            let string_offset =
+                buf.pread_with::<u32>(off, LE).unwrap_or_default() as usize;
+            let string_start = code_start + string_offset;
+            if string_start <= off || string_start >= buf.len() {
+                continue;
+            }
+            if let Some(len) = buf[string_start..].iter().position(|b| *b == 0)
+            {
+                strings.insert(string_offset, string_start + len);
+                text_start = text_start.min(string_start);
+                off += 4;
+            }
+        }
+        strings
+    }
+}
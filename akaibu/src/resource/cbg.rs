@@ -0,0 +1,292 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use image::{buffer::ConvertBuffer, ImageBuffer, RgbaImage};
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+const HEADER_SIZE: usize = 0x30;
+const LEAF_COUNT: usize = 0x100;
+
+#[derive(Debug, Clone)]
+pub(crate) enum CbgScheme {
+    Universal,
+}
+
+#[derive(Debug, Pread)]
+struct CbgHeader {
+    magic: [u8; 16],
+    width: u16,
+    height: u16,
+    bpp: u32,
+    unk: [u8; 8],
+    intermediate_size: u32,
+    key: u32,
+    encoded_size: u32,
+    checksum_sum: u8,
+    checksum_xor: u8,
+    version: u16,
+}
+
+impl ResourceScheme for CbgScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[CBG] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl CbgScheme {
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        let header = buf.pread_with::<CbgHeader>(0, LE)?;
+        log::debug!("Header: {:?}", header);
+        if header.version > 1 {
+            return Err(AkaibuError::Unimplemented(format!(
+                "Unsupported CompressedBG version {}",
+                header.version
+            ))
+            .into());
+        }
+        let pixel_size = header.bpp as usize / 8;
+        if pixel_size != 1 && pixel_size != 3 && pixel_size != 4 {
+            return Err(AkaibuError::Unimplemented(format!(
+                "Unsupported CompressedBG bpp {}",
+                header.bpp
+            ))
+            .into());
+        }
+
+        let encoded_end = HEADER_SIZE + header.encoded_size as usize;
+        let mut encoded = buf
+            .get(HEADER_SIZE..encoded_end)
+            .context("Out of bounds access")?
+            .to_vec();
+        Self::decrypt(&mut encoded, &header)?;
+        let off = &mut 0;
+        let mut weights = Vec::with_capacity(LEAF_COUNT);
+        for _ in 0..LEAF_COUNT {
+            weights.push(read_integer(&encoded, off)?);
+        }
+
+        let tree = Self::create_tree(&weights)?;
+        let packed = Self::huffman_decompress(
+            &tree,
+            &buf[encoded_end..],
+            header.intermediate_size as usize,
+        )?;
+        let width = header.width as usize;
+        let height = header.height as usize;
+        let mut pixels =
+            Self::unpack_zeros(&packed, width * height * pixel_size)?;
+        Self::reverse_average_sampling(&mut pixels, width, height, pixel_size);
+
+        let (width, height) = (header.width as u32, header.height as u32);
+        let image: RgbaImage = match pixel_size {
+            1 => {
+                let image: ImageBuffer<image::Luma<u8>, Vec<u8>> =
+                    ImageBuffer::from_vec(width, height, pixels)
+                        .context("Invalid image resolution")?;
+                image.convert()
+            }
+            3 => {
+                let image: ImageBuffer<image::Bgr<u8>, Vec<u8>> =
+                    ImageBuffer::from_vec(width, height, pixels)
+                        .context("Invalid image resolution")?;
+                image.convert()
+            }
+            _ => {
+                let image: ImageBuffer<image::Bgra<u8>, Vec<u8>> =
+                    ImageBuffer::from_vec(width, height, pixels)
+                        .context("Invalid image resolution")?;
+                image.convert()
+            }
+        };
+        Ok(ResourceType::RgbaImage { image })
+    }
+    fn decrypt(buf: &mut [u8], header: &CbgHeader) -> anyhow::Result<()> {
+        let mut key = header.key;
+        let mut sum = 0u8;
+        let mut xor = 0u8;
+        for b in buf.iter_mut() {
+            *b = b.wrapping_sub(update_key(&mut key));
+            sum = sum.wrapping_add(*b);
+            xor ^= *b;
+        }
+        if sum != header.checksum_sum || xor != header.checksum_xor {
+            return Err(AkaibuError::Custom(
+                "CompressedBG checksum mismatch".to_owned(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+    /// Build Huffman tree from leaf weights, parent nodes are appended
+    /// after leaves and last node is root
+    fn create_tree(weights: &[u32]) -> anyhow::Result<Vec<CbgNode>> {
+        let mut nodes = weights
+            .iter()
+            .map(|weight| CbgNode {
+                valid: *weight != 0,
+                weight: *weight,
+                children: None,
+            })
+            .collect::<Vec<_>>();
+        let root_weight = weights.iter().map(|w| *w as u64).sum::<u64>();
+        loop {
+            let mut children = [0; 2];
+            let mut weight = 0u64;
+            for child in children.iter_mut() {
+                let (index, node) = nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, node)| node.valid)
+                    .min_by_key(|(i, node)| (node.weight, *i))
+                    .context("Invalid CompressedBG Huffman weights")?;
+                weight += node.weight as u64;
+                *child = index;
+                nodes[index].valid = false;
+            }
+            nodes.push(CbgNode {
+                valid: true,
+                weight: weight as u32,
+                children: Some((children[0], children[1])),
+            });
+            if weight >= root_weight {
+                return Ok(nodes);
+            }
+        }
+    }
+    fn huffman_decompress(
+        tree: &[CbgNode],
+        src: &[u8],
+        dest_size: usize,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut dest = Vec::with_capacity(dest_size);
+        let mut bit_index = 0;
+        while dest.len() < dest_size {
+            let mut node_index = tree.len() - 1;
+            while let Some((left, right)) = tree[node_index].children {
+                let byte = src
+                    .get(bit_index / 8)
+                    .context("Unexpected end of CompressedBG data")?;
+                let bit = (byte >> (7 - bit_index % 8)) & 1;
+                bit_index += 1;
+                node_index = if bit == 0 { left } else { right };
+            }
+            dest.push(node_index as u8);
+        }
+        Ok(dest)
+    }
+    /// Expand alternating runs of copied bytes and zeros
+    fn unpack_zeros(src: &[u8], dest_size: usize) -> anyhow::Result<Vec<u8>> {
+        let mut dest = Vec::with_capacity(dest_size);
+        let off = &mut 0;
+        let mut zeros = false;
+        while dest.len() < dest_size && *off < src.len() {
+            let count = read_integer(src, off)? as usize;
+            if dest.len() + count > dest_size {
+                break;
+            }
+            if zeros {
+                dest.resize(dest.len() + count, 0);
+            } else {
+                dest.extend_from_slice(
+                    src.get(*off..*off + count)
+                        .context("Out of bounds access")?,
+                );
+                *off += count;
+            }
+            zeros = !zeros;
+        }
+        dest.resize(dest_size, 0);
+        Ok(dest)
+    }
+    /// Each channel is stored as difference to average of left and top pixel
+    fn reverse_average_sampling(
+        buf: &mut [u8],
+        width: usize,
+        height: usize,
+        pixel_size: usize,
+    ) {
+        let stride = width * pixel_size;
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = y * stride + x * pixel_size;
+                for p in pixel..pixel + pixel_size {
+                    let mut avg = 0u32;
+                    if x > 0 {
+                        avg += buf[p - pixel_size] as u32;
+                    }
+                    if y > 0 {
+                        avg += buf[p - stride] as u32;
+                    }
+                    if x > 0 && y > 0 {
+                        avg /= 2;
+                    }
+                    buf[p] = buf[p].wrapping_add(avg as u8);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CbgNode {
+    valid: bool,
+    weight: u32,
+    children: Option<(usize, usize)>,
+}
+
+fn update_key(key: &mut u32) -> u8 {
+    let v0 = 20021u32.wrapping_mul(*key & 0xFFFF);
+    let v1 = 20021u32
+        .wrapping_mul(*key >> 16)
+        .wrapping_add(key.wrapping_mul(346))
+        .wrapping_add(v0 >> 16);
+    *key = (v1 << 16).wrapping_add(v0 & 0xFFFF).wrapping_add(1);
+    v1 as u8
+}
+
+/// Variable length integer, 7 bits per byte with continuation in high bit
+fn read_integer(buf: &[u8], off: &mut usize) -> anyhow::Result<u32> {
+    let mut ret = 0u32;
+    let mut shift = 0;
+    loop {
+        let code = buf.gread::<u8>(off)?;
+        ret |= ((code & 0x7F) as u32).wrapping_shl(shift);
+        shift += 7;
+        if code & 0x80 == 0 {
+            return Ok(ret);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::{ResourceScheme, ResourceType};
-use crate::{error::AkaibuError, util::image::resolve_color_table};
+use crate::{
+    error::AkaibuError, options::Options, util::image::resolve_color_table,
+};
 use anyhow::Context;
 use image::{buffer::ConvertBuffer, ImageBuffer};
 use scroll::{Pread, LE};
@@ -18,7 +20,11 @@ struct G00Header {
 }
 
 impl ResourceScheme for G00Scheme {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType> {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
         file.read_to_end(&mut buf)?;
@@ -29,6 +35,7 @@ impl ResourceScheme for G00Scheme {
         &self,
         _file_path: &PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         self.from_bytes(buf)
     }
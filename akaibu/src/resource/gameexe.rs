@@ -0,0 +1,75 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{options::Options, scheme::siglus::decrypt_and_decompress};
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+#[derive(Debug, Clone)]
+pub(crate) enum GameexeScheme {
+    Universal,
+}
+
+#[derive(Debug, Pread)]
+struct GameexeHeader {
+    version: u32,
+    exe_angou_mode: u32,
+}
+
+impl ResourceScheme for GameexeScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 16);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf, options)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf, options)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[GAMEEXE] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl GameexeScheme {
+    fn from_bytes(
+        &self,
+        mut buf: Vec<u8>,
+        options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let header = buf.pread_with::<GameexeHeader>(0, LE)?;
+        log::debug!("Header: {:?}", header);
+        let game_key = options.siglus.get_game_key(header.exe_angou_mode)?;
+        let data = decrypt_and_decompress(&mut buf[8..], game_key.as_ref())?;
+        let text = data
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<u16>>();
+        Ok(ResourceType::Text(
+            String::from_utf16_lossy(&text)
+                .trim_start_matches('\u{FEFF}')
+                .to_owned(),
+        ))
+    }
+}
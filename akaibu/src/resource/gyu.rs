@@ -1,6 +1,7 @@
 use super::{ResourceScheme, ResourceType};
 use crate::{
     error::AkaibuError,
+    options::Options,
     util::{image::bitmap_to_png_with_padding, mt::MT19937},
 };
 use anyhow::Context;
@@ -57,7 +58,11 @@ pub(crate) enum GyuScheme {
 }
 
 impl ResourceScheme for GyuScheme {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType> {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
         file.read_to_end(&mut buf)?;
@@ -67,11 +72,51 @@ impl ResourceScheme for GyuScheme {
         &self,
         file_path: &PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         self.from_bytes(buf, file_path)
     }
     fn get_name(&self) -> String {
-        format!("[GYU] {}",match self {
+        format!("[GYU] {}", self.get_title())
+    }
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        Self::get_games()
+            .into_iter()
+            .map(|game| Box::new(game) as Box<dyn ResourceScheme>)
+            .collect()
+    }
+}
+
+impl GyuScheme {
+    /// All supported games, shared with RLD scripts of the same games
+    pub(crate) fn get_games() -> Vec<Self> {
+        vec![
+            Self::DemonBusters,
+            Self::HakoniwaLogic,
+            Self::HoshizoraTeaParty,
+            Self::Imopara1,
+            Self::Imopara2,
+            Self::Imopara3,
+            Self::KagiTori,
+            Self::KaraNoShoujo,
+            Self::KonekoNekoNeko,
+            Self::LoveLoveLife,
+            Self::Ojousama,
+            Self::OpenWorld,
+            Self::TsukiNoShoujo,
+            Self::UchiNoImouto,
+            Self::UchiNoKoibito,
+            Self::Yuuwaku,
+            Self::WanNyan,
+            Self::NyanCafe,
+            Self::Universal,
+        ]
+    }
+    pub(crate) fn get_title(&self) -> &str {
+        match self {
             Self::DemonBusters => "Demon Busters ~Ecchi na Ecchi na Demon Taiji~",
             Self::HakoniwaLogic => "Hakoniwa Logic",
             Self::HoshizoraTeaParty => "Hoshizora Tea Party",
@@ -92,37 +137,7 @@ impl ResourceScheme for GyuScheme {
             Self::NyanCafe => "Nyan Café Macchiato ~Neko ga Iru Café no Ecchi Jijou~",
             Self::Universal => "Universal"
         }
-                )
     }
-    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
-    where
-        Self: Sized,
-    {
-        vec![
-            Box::new(Self::DemonBusters),
-            Box::new(Self::HakoniwaLogic),
-            Box::new(Self::HoshizoraTeaParty),
-            Box::new(Self::Imopara1),
-            Box::new(Self::Imopara2),
-            Box::new(Self::Imopara3),
-            Box::new(Self::KagiTori),
-            Box::new(Self::KaraNoShoujo),
-            Box::new(Self::KonekoNekoNeko),
-            Box::new(Self::LoveLoveLife),
-            Box::new(Self::Ojousama),
-            Box::new(Self::OpenWorld),
-            Box::new(Self::TsukiNoShoujo),
-            Box::new(Self::UchiNoImouto),
-            Box::new(Self::UchiNoKoibito),
-            Box::new(Self::Yuuwaku),
-            Box::new(Self::WanNyan),
-            Box::new(Self::NyanCafe),
-            Box::new(Self::Universal),
-        ]
-    }
-}
-
-impl GyuScheme {
     fn from_bytes(
         &self,
         mut buf: Vec<u8>,
@@ -192,7 +207,7 @@ impl GyuScheme {
             .get(self.get_key())
             .context(format!("Unsupported game key {}", self.get_key()))?)
     }
-    fn get_key(&self) -> &str {
+    pub(crate) fn get_key(&self) -> &str {
         match self {
             Self::DemonBusters => "demonbusters",
             Self::HakoniwaLogic => "hakoniwalogic",
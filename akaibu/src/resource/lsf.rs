@@ -0,0 +1,184 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use encoding_rs::SHIFT_JIS;
+use image::{imageops, RgbaImage};
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+const HEADER_SIZE: usize = 0x1C;
+const LAYER_SIZE: usize = 0xA4;
+const LAYER_NAME_SIZE: usize = 0x40;
+
+#[derive(Debug, Clone)]
+pub(crate) enum LsfScheme {
+    Universal,
+}
+
+#[derive(Debug, Pread)]
+struct LsfHeader {
+    magic: [u8; 4],
+    version: u16,
+    layer_count: u16,
+    width: u32,
+    height: u32,
+    unk: [u8; 12],
+}
+
+#[derive(Debug)]
+struct LsfLayer {
+    name: String,
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+impl ResourceScheme for LsfScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 16);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf, file_path)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf, file_path)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[LSF] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl LsfScheme {
+    /// Layers are PNG files stored next to LSF file, each layer is placed
+    /// at its position on canvas of LSF size. When layers are not available
+    /// on disk, e.g. LSF is converted straight from archive, list of layers
+    /// with their positions is returned instead.
+    fn from_bytes(
+        &self,
+        buf: Vec<u8>,
+        file_path: &PathBuf,
+    ) -> anyhow::Result<ResourceType> {
+        let header = buf.pread_with::<LsfHeader>(0, LE)?;
+        log::debug!("Header: {:?}", header);
+        let layers = (0..header.layer_count as usize)
+            .map(|i| Self::parse_layer(&buf, HEADER_SIZE + i * LAYER_SIZE))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        log::debug!("Layers: {:#?}", layers);
+
+        match Self::compose_layers(&header, &layers, file_path) {
+            Ok(sprites) => Ok(ResourceType::SpriteSheet { sprites }),
+            Err(err) => {
+                log::warn!("{}, writing layer positions instead", err);
+                Ok(ResourceType::Text(layers.iter().fold(
+                    "name\tleft\ttop\tright\tbottom\n".to_owned(),
+                    |mut text, layer| {
+                        text += &format!(
+                            "{}\t{}\t{}\t{}\t{}\n",
+                            layer.name,
+                            layer.left,
+                            layer.top,
+                            layer.right,
+                            layer.bottom
+                        );
+                        text
+                    },
+                )))
+            }
+        }
+    }
+    fn compose_layers(
+        header: &LsfHeader,
+        layers: &[LsfLayer],
+        file_path: &PathBuf,
+    ) -> anyhow::Result<Vec<RgbaImage>> {
+        let directory = file_path
+            .parent()
+            .context("Could not get parent directory")?;
+        layers
+            .iter()
+            .map(|layer| {
+                let mut layer_path = directory.join(&layer.name);
+                layer_path.set_extension("png");
+                let image = image::open(&layer_path)
+                    .map_err(|err| {
+                        AkaibuError::Custom(format!(
+                            "Could not load LSF layer {:?}: {}",
+                            layer_path, err
+                        ))
+                    })?
+                    .to_rgba8();
+                let mut sprite = RgbaImage::new(header.width, header.height);
+                Self::place_layer(&mut sprite, &image, layer);
+                Ok(sprite)
+            })
+            .collect()
+    }
+    /// Overlay layer image clipped to its rectangle and canvas, parts at
+    /// negative offsets are cropped
+    fn place_layer(
+        canvas: &mut RgbaImage,
+        image: &RgbaImage,
+        layer: &LsfLayer,
+    ) {
+        let left = layer.left as i64;
+        let top = layer.top as i64;
+        let right = (layer.right as i64)
+            .min(left + image.width() as i64)
+            .min(canvas.width() as i64);
+        let bottom = (layer.bottom as i64)
+            .min(top + image.height() as i64)
+            .min(canvas.height() as i64);
+        let x = left.max(0);
+        let y = top.max(0);
+        if right <= x || bottom <= y {
+            return;
+        }
+        let visible = imageops::crop_imm(
+            image,
+            (x - left) as u32,
+            (y - top) as u32,
+            (right - x) as u32,
+            (bottom - y) as u32,
+        );
+        imageops::overlay(canvas, &visible, x as u32, y as u32);
+    }
+    fn parse_layer(buf: &[u8], offset: usize) -> anyhow::Result<LsfLayer> {
+        let name = buf
+            .get(offset..offset + LAYER_NAME_SIZE)
+            .context("Out of bounds access")?;
+        let name_len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+        let (name, _, _) = SHIFT_JIS.decode(&name[..name_len]);
+        let off = &mut (offset + LAYER_NAME_SIZE);
+        Ok(LsfLayer {
+            name: name.into_owned(),
+            left: buf.gread_with::<i32>(off, LE)?,
+            top: buf.gread_with::<i32>(off, LE)?,
+            right: buf.gread_with::<i32>(off, LE)?,
+            bottom: buf.gread_with::<i32>(off, LE)?,
+        })
+    }
+}
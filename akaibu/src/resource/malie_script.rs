@@ -0,0 +1,137 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+/// Control code preceding inline commands like voice or ruby
+const CONTROL_CODE: u16 = 0x07;
+
+#[derive(Debug, Clone)]
+pub(crate) enum MalieScriptScheme {
+    Universal,
+}
+
+impl ResourceScheme for MalieScriptScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[MALIE SCRIPT] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl MalieScriptScheme {
+    /// Dump message table, one message per line prefixed with its index
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        let (index_offset, data_offset) = Self::find_message_table(&buf)
+            .ok_or_else(|| {
+                AkaibuError::Custom(
+                    "Could not find Malie message table".to_owned(),
+                )
+            })?;
+        let message_count = buf.pread_with::<u32>(index_offset, LE)? as usize;
+        let off = &mut (index_offset + 4);
+        let mut text = String::with_capacity(buf.len() - data_offset);
+        for i in 0..message_count {
+            let offset = buf.gread_with::<u32>(off, LE)? as usize;
+            let length = buf.gread_with::<u32>(off, LE)? as usize;
+            let start = data_offset + offset;
+            let message = buf
+                .get(start..start + length)
+                .context("Out of bounds access")?
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<u16>>();
+            text.push_str(&format!(
+                "{:05}\t{}\n",
+                i,
+                Self::format_message(&message)
+            ));
+        }
+        Ok(ResourceType::Text(text))
+    }
+    /// Message table is last section of script: message count, offset and
+    /// length pairs, data size and UTF-16 data till end of file
+    fn find_message_table(buf: &[u8]) -> Option<(usize, usize)> {
+        (8..buf.len().saturating_sub(4)).find_map(|size_offset| {
+            let data_size = buf.pread_with::<u32>(size_offset, LE).ok()?;
+            if data_size == 0
+                || data_size as usize != buf.len() - size_offset - 4
+            {
+                return None;
+            }
+            (1..=(size_offset - 4) / 8).find_map(|count| {
+                let index_offset = size_offset - count * 8 - 4;
+                if buf.pread_with::<u32>(index_offset, LE).ok()? as usize
+                    != count
+                {
+                    return None;
+                }
+                let valid = (0..count).all(|i| {
+                    let entry = index_offset + 4 + i * 8;
+                    match (
+                        buf.pread_with::<u32>(entry, LE),
+                        buf.pread_with::<u32>(entry + 4, LE),
+                    ) {
+                        (Ok(offset), Ok(length)) => {
+                            offset as u64 + length as u64 <= data_size as u64
+                        }
+                        _ => false,
+                    }
+                });
+                if valid {
+                    Some((index_offset, size_offset + 4))
+                } else {
+                    None
+                }
+            })
+        })
+    }
+    /// Escape line breaks and drop inline control sequences
+    fn format_message(message: &[u16]) -> String {
+        let mut result = String::with_capacity(message.len());
+        let mut chars = std::char::decode_utf16(message.iter().copied())
+            .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER));
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' => result.push_str("\\n"),
+                c if c as u32 == CONTROL_CODE as u32 => {
+                    chars.next();
+                }
+                c if c.is_control() => (),
+                c => result.push(c),
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,135 @@
+use super::{ResourceScheme, ResourceType};
+use crate::options::Options;
+use encoding_rs::SHIFT_JIS;
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+/// Text with full width characters stored as their index in SHIFT_JIS table
+const OPCODE_STRING_ENCRYPTED: u8 = 0x0A;
+/// Plain SHIFT_JIS text
+const OPCODE_STRING: u8 = 0x0B;
+/// Number of valid SHIFT_JIS trail bytes
+const TRAIL_BYTE_COUNT: u16 = 0xBC;
+
+#[derive(Debug, Clone)]
+pub(crate) enum MesScheme {
+    Universal,
+}
+
+impl ResourceScheme for MesScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 16);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[MES] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl MesScheme {
+    /// Dump text pushed by string opcodes, one per line prefixed with
+    /// opcode offset relative to start of bytecode
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        // Bytecode is preceded by table of message offsets
+        let message_count = buf.pread_with::<u32>(0, LE)? as usize;
+        let code_start = match message_count.checked_mul(4) {
+            Some(size) if size + 4 <= buf.len() => size + 4,
+            _ => 0,
+        };
+        let code = &buf[code_start..];
+        let mut text = String::new();
+        let mut off = 0;
+        while off < code.len() {
+            let opcode = code[off];
+            if opcode != OPCODE_STRING_ENCRYPTED && opcode != OPCODE_STRING {
+                off += 1;
+                continue;
+            }
+            let string = match code[off + 1..].iter().position(|b| *b == 0) {
+                Some(len) => &code[off + 1..off + 1 + len],
+                None => break,
+            };
+            let raw = if opcode == OPCODE_STRING_ENCRYPTED {
+                Self::decrypt_string(string)
+            } else {
+                Some(string.to_vec())
+            };
+            match raw.and_then(|raw| Self::decode(&raw)) {
+                Some(line) => {
+                    text.push_str(&format!("{:08X}\t{}\n", off, line));
+                    off += string.len() + 2;
+                }
+                None => off += 1,
+            }
+        }
+        Ok(ResourceType::Text(text))
+    }
+    /// Lead byte below 0x81 marks big endian character index, other
+    /// characters are stored as they are
+    fn decrypt_string(buf: &[u8]) -> Option<Vec<u8>> {
+        let mut result = Vec::with_capacity(buf.len());
+        let mut off = 0;
+        while off < buf.len() {
+            let lead = buf[off];
+            if lead >= 0x81 {
+                result.extend_from_slice(buf.get(off..off + 2)?);
+            } else {
+                let index = u16::from_be_bytes([lead, *buf.get(off + 1)?]);
+                let mut high = index / TRAIL_BYTE_COUNT + 0x81;
+                let mut low = index % TRAIL_BYTE_COUNT + 0x40;
+                if high > 0x9F {
+                    high += 0x40;
+                }
+                if low >= 0x7F {
+                    low += 1;
+                }
+                if high > 0xFC {
+                    return None;
+                }
+                result.push(high as u8);
+                result.push(low as u8);
+            }
+            off += 2;
+        }
+        Some(result)
+    }
+    fn decode(buf: &[u8]) -> Option<String> {
+        let (text, _, had_errors) = SHIFT_JIS.decode(buf);
+        if had_errors
+            || text.chars().any(char::is_control)
+            || text.chars().all(|c| c.is_ascii())
+        {
+            None
+        } else {
+            Some(text.into_owned())
+        }
+    }
+}
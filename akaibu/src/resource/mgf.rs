@@ -0,0 +1,65 @@
+use super::{ResourceScheme, ResourceType};
+use crate::options::Options;
+use anyhow::Context;
+use std::{fs::File, io::Read, path::PathBuf};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+#[derive(Debug, Clone)]
+pub(crate) enum MgfScheme {
+    Universal,
+}
+
+impl ResourceScheme for MgfScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[MGF] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl MgfScheme {
+    /// MGF is PNG with `MalieGF\0` in place of PNG signature
+    fn from_bytes(&self, mut buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        buf.get_mut(..PNG_SIGNATURE.len())
+            .context("Out of bounds access")?
+            .copy_from_slice(PNG_SIGNATURE);
+        Ok(ResourceType::RgbaImage {
+            image: image::load_from_memory_with_format(
+                &buf,
+                image::ImageFormat::Png,
+            )?
+            .to_rgba8(),
+        })
+    }
+}
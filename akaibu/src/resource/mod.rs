@@ -1,16 +1,29 @@
+pub(crate) mod acv1_script;
 mod akb;
+mod audio;
+mod bgi_script;
+mod cbg;
 mod g00;
+mod gameexe;
 mod gyu;
 mod jbp1;
+mod lsf;
+mod malie_script;
+mod mes;
+mod mgf;
+mod nwa;
 mod pb3b;
+mod rld;
 mod tlg;
+mod ybn;
 mod ycg;
 
+use crate::options::Options;
 use dyn_clone::DynClone;
 use enum_iterator::IntoEnumIterator;
 use image::RgbaImage;
 use scroll::{Pread, LE};
-use std::{fmt::Debug, path::PathBuf};
+use std::{fmt::Debug, path::PathBuf, time::Duration};
 use tlg::TlgScheme;
 
 #[derive(Debug, IntoEnumIterator)]
@@ -22,15 +35,37 @@ pub enum ResourceMagic {
     GYU,
     GYUUniversal,
     G00,
+    CBG,
+    BgiScript,
+    Gameexe,
+    NWA,
+    MGF,
+    MalieScript,
+    YBN,
+    LSF,
+    MES,
+    RLD,
+    /// Has no magic, unnamed scripts are extracted from ACV1 archives with
+    /// `acvs` extension
+    Acv1Script,
+    WAV,
+    OGG,
+    /// BURIKO sound with `bw  ` header
+    BurikoSound,
     Unrecognized,
 }
 
 pub trait ResourceScheme: Debug + Send + Sync + DynClone {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType>;
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        options: &Options,
+    ) -> anyhow::Result<ResourceType>;
     fn convert_from_bytes(
         &self,
         file_path: &PathBuf,
         buf: Vec<u8>,
+        options: &Options,
     ) -> anyhow::Result<ResourceType>;
     fn get_name(&self) -> String;
     fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
@@ -49,8 +84,28 @@ impl ResourceMagic {
             [80, 66, 51, 66, ..] => Self::PB3B,
             // YCG\x00
             [89, 67, 71, 0, ..] => Self::YCG,
+            // YSTB
+            [89, 83, 84, 66, ..] => Self::YBN,
+            // LSF\x00
+            [76, 83, 70, 0, ..] => Self::LSF,
+            // \x00DLR
+            [0, 68, 76, 82, ..] => Self::RLD,
             // AKB or AKB+
             [65, 75, 66, 32, ..] | [65, 75, 66, 43, ..] => Self::AKB,
+            // CompressedBG___
+            [67, 111, 109, 112, 114, 101, 115, 115, 101, 100, 66, 71, ..] => {
+                Self::CBG
+            }
+            // BurikoCompiledScriptVer1.00
+            [66, 117, 114, 105, 107, 111, 67, 111, 109, 112, 105, 108, 101, 100, 83, 99, ..] => {
+                Self::BgiScript
+            }
+            // MalieGF\x00
+            [77, 97, 108, 105, 101, 71, 70, 0, ..] => Self::MGF,
+            // RIFF....WAVE
+            [82, 73, 70, 70, _, _, _, _, 87, 65, 86, 69, ..] => Self::WAV,
+            // OggS
+            [79, 103, 103, 83, ..] => Self::OGG,
             // GYU\x1a
             [71, 89, 85, 26, ..] => match buf.pread_with::<u32>(8, LE) {
                 Ok(mt_seed) => {
@@ -62,14 +117,30 @@ impl ResourceMagic {
                 }
                 Err(_) => Self::Unrecognized,
             },
+            // ....bw\x20\x20
+            [_, _, _, _, 98, 119, 32, 32, ..] => Self::BurikoSound,
             _ => Self::Unrecognized,
         }
     }
     pub fn parse_file_extension(file_path: &PathBuf) -> Self {
+        if let Some(file_name) = file_path.file_name() {
+            if file_name
+                .to_string_lossy()
+                .eq_ignore_ascii_case("gameexe.dat")
+            {
+                return Self::Gameexe;
+            }
+            if file_name.to_string_lossy().eq_ignore_ascii_case("exec.dat") {
+                return Self::MalieScript;
+            }
+        }
         match file_path.extension() {
             Some(extension) => match extension.to_str() {
                 Some(extension) => match extension {
                     "g00" => Self::G00,
+                    "nwa" => Self::NWA,
+                    "mes" | "MES" => Self::MES,
+                    "acvs" => Self::Acv1Script,
                     _ => Self::Unrecognized,
                 },
                 None => Self::Unrecognized,
@@ -86,6 +157,20 @@ impl ResourceMagic {
             Self::GYU => false,
             Self::GYUUniversal => true,
             Self::G00 => true,
+            Self::CBG => true,
+            Self::BgiScript => true,
+            Self::Gameexe => true,
+            Self::NWA => true,
+            Self::MGF => true,
+            Self::MalieScript => true,
+            Self::YBN => true,
+            Self::LSF => true,
+            Self::MES => true,
+            Self::RLD => false,
+            Self::Acv1Script => true,
+            Self::WAV => true,
+            Self::OGG => true,
+            Self::BurikoSound => true,
             Self::Unrecognized => true,
         }
     }
@@ -100,6 +185,26 @@ impl ResourceMagic {
                 vec![Box::new(gyu::GyuScheme::Universal)]
             }
             ResourceMagic::G00 => g00::G00Scheme::get_schemes(),
+            ResourceMagic::CBG => cbg::CbgScheme::get_schemes(),
+            ResourceMagic::BgiScript => {
+                bgi_script::BgiScriptScheme::get_schemes()
+            }
+            ResourceMagic::Gameexe => gameexe::GameexeScheme::get_schemes(),
+            ResourceMagic::NWA => nwa::NwaScheme::get_schemes(),
+            ResourceMagic::MGF => mgf::MgfScheme::get_schemes(),
+            ResourceMagic::MalieScript => {
+                malie_script::MalieScriptScheme::get_schemes()
+            }
+            ResourceMagic::YBN => ybn::YbnScheme::get_schemes(),
+            ResourceMagic::LSF => lsf::LsfScheme::get_schemes(),
+            ResourceMagic::MES => mes::MesScheme::get_schemes(),
+            ResourceMagic::RLD => rld::RldScheme::get_schemes(),
+            ResourceMagic::Acv1Script => {
+                acv1_script::Acv1ScriptScheme::get_schemes()
+            }
+            ResourceMagic::WAV
+            | ResourceMagic::OGG
+            | ResourceMagic::BurikoSound => audio::AudioScheme::get_schemes(),
             ResourceMagic::Unrecognized => vec![],
         }
     }
@@ -113,8 +218,56 @@ impl ResourceMagic {
 
 #[derive(Debug, Clone)]
 pub enum ResourceType {
-    SpriteSheet { sprites: Vec<RgbaImage> },
-    RgbaImage { image: RgbaImage },
+    SpriteSheet {
+        sprites: Vec<RgbaImage>,
+    },
+    RgbaImage {
+        image: RgbaImage,
+    },
     Text(String),
+    /// Decrypted script along with text extracted from it
+    Script {
+        data: Vec<u8>,
+        text: String,
+    },
+    /// Complete audio file, either RIFF/WAVE or Ogg depending on codec
+    Audio {
+        codec: AudioCodec,
+        sample_rate: u32,
+        channels: u16,
+        duration: Duration,
+        data: Vec<u8>,
+    },
     Other,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub enum AudioCodec {
+    Wav,
+    Vorbis,
+    Opus,
+}
+
+impl AudioCodec {
+    /// Extension of container the codec is stored in
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Vorbis | Self::Opus => "ogg",
+        }
+    }
+}
+
+impl std::fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Wav => "WAV",
+                Self::Vorbis => "Vorbis",
+                Self::Opus => "Opus",
+            }
+        )
+    }
+}
@@ -0,0 +1,289 @@
+use super::{audio, ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+const HEADER_SIZE: usize = 0x2C;
+/// Level used for uncompressed data
+const LEVEL_UNCOMPRESSED: i32 = -1;
+const MAX_COMPRESSION_LEVEL: i32 = 5;
+
+#[derive(Debug, Clone)]
+pub(crate) enum NwaScheme {
+    Universal,
+}
+
+#[derive(Debug, Pread)]
+struct NwaHeader {
+    channels: u16,
+    bits_per_sample: u16,
+    sample_rate: u32,
+    compression_level: i32,
+    use_run_length: i32,
+    block_count: u32,
+    data_size: u32,
+    compressed_data_size: u32,
+    sample_count: u32,
+    block_size: u32,
+    rest_size: u32,
+    unk: u32,
+}
+
+impl ResourceScheme for NwaScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[NWA] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl NwaScheme {
+    fn from_bytes(&self, buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        let header = buf.pread_with::<NwaHeader>(0, LE)?;
+        log::debug!("Header: {:#?}", header);
+        if header.channels == 0
+            || header.channels > 2
+            || (header.bits_per_sample != 8 && header.bits_per_sample != 16)
+        {
+            return Err(AkaibuError::Unimplemented(format!(
+                "NWA with {} channels and {} bits per sample",
+                header.channels, header.bits_per_sample
+            ))
+            .into());
+        }
+        if header.compression_level != LEVEL_UNCOMPRESSED
+            && !(0..=MAX_COMPRESSION_LEVEL).contains(&header.compression_level)
+        {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid NWA compression level: {}",
+                header.compression_level
+            ))
+            .into());
+        }
+        let pcm = if header.compression_level == LEVEL_UNCOMPRESSED {
+            buf.get(HEADER_SIZE..HEADER_SIZE + header.data_size as usize)
+                .context("Out of bounds access")?
+                .to_vec()
+        } else {
+            Self::decompress(&buf, &header)?
+        };
+        audio::from_wav(pcm_to_wav(
+            &pcm,
+            header.channels,
+            header.sample_rate,
+            header.bits_per_sample,
+        ))
+    }
+    fn decompress(buf: &[u8], header: &NwaHeader) -> anyhow::Result<Vec<u8>> {
+        let bytes_per_sample = header.bits_per_sample as usize / 8;
+        let mut off = HEADER_SIZE;
+        if header.block_count as usize * 4 > buf.len() - HEADER_SIZE {
+            return Err(AkaibuError::Custom(format!(
+                "NWA block table with {} blocks exceeds file size",
+                header.block_count
+            ))
+            .into());
+        }
+        let mut block_offsets = Vec::with_capacity(header.block_count as usize);
+        for _ in 0..header.block_count {
+            block_offsets.push(buf.gread_with::<u32>(&mut off, LE)? as usize);
+        }
+        let mut pcm = Vec::with_capacity(header.data_size as usize);
+        for (i, block_offset) in block_offsets.iter().enumerate() {
+            let block_end = block_offsets
+                .get(i + 1)
+                .copied()
+                .unwrap_or(header.compressed_data_size as usize);
+            if *block_offset > block_end
+                || block_end > header.compressed_data_size as usize
+            {
+                return Err(AkaibuError::Custom(format!(
+                    "Invalid NWA block {} range: {:#X}..{:#X}",
+                    i, block_offset, block_end
+                ))
+                .into());
+            }
+            let block = buf
+                .get(*block_offset..block_end)
+                .context("Out of bounds access")?;
+            let sample_count = if i + 1 == block_offsets.len() {
+                header.rest_size
+            } else {
+                header.block_size
+            } as usize;
+            Self::decompress_block(
+                block,
+                header,
+                sample_count * bytes_per_sample,
+                &mut pcm,
+            )?;
+        }
+        Ok(pcm)
+    }
+    fn decompress_block(
+        block: &[u8],
+        header: &NwaHeader,
+        block_size: usize,
+        dest: &mut Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let level = header.compression_level;
+        let stereo = header.channels == 2;
+        let wide = header.bits_per_sample == 16;
+        let off = &mut 0;
+        let mut sample = [0i32; 2];
+        for channel in 0..header.channels as usize {
+            sample[channel] = if wide {
+                block.gread_with::<i16>(off, LE)? as i32
+            } else {
+                block.gread::<u8>(off)? as i32
+            };
+        }
+        let mut reader = BitReader::new(&block[*off..]);
+        let mut channel = 0;
+        let mut run_length = 0;
+        for _ in 0..block_size / (header.bits_per_sample as usize / 8) {
+            if reader.is_empty() {
+                break;
+            }
+            if run_length == 0 {
+                let kind = reader.read(3);
+                if kind == 7 {
+                    if reader.read(1) == 1 {
+                        sample[channel] = 0;
+                    } else {
+                        let (bits, shift) = if level >= 3 {
+                            (8, 9)
+                        } else {
+                            (8 - level as u32, 9 + level as u32)
+                        };
+                        sample[channel] +=
+                            Self::read_delta(&mut reader, bits, shift);
+                    }
+                } else if kind != 0 {
+                    let (bits, shift) = if level >= 3 {
+                        (level as u32 + 3, 1 + kind)
+                    } else {
+                        (5 - level as u32, 2 + kind + level as u32)
+                    };
+                    sample[channel] +=
+                        Self::read_delta(&mut reader, bits, shift);
+                } else if header.use_run_length != 0 {
+                    run_length = reader.read(1);
+                    if run_length == 1 {
+                        run_length = reader.read(2);
+                        if run_length == 3 {
+                            run_length = reader.read(8);
+                        }
+                    }
+                }
+            } else {
+                run_length -= 1;
+            }
+            if wide {
+                dest.extend_from_slice(&(sample[channel] as i16).to_le_bytes());
+            } else {
+                dest.push(sample[channel] as u8);
+            }
+            if stereo {
+                channel ^= 1;
+            }
+        }
+        Ok(())
+    }
+    fn read_delta(reader: &mut BitReader<'_>, bits: u32, shift: u32) -> i32 {
+        let value = reader.read(bits);
+        let magnitude = ((value & ((1 << (bits - 1)) - 1)) << shift) as i32;
+        if value & (1 << (bits - 1)) != 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BitReader<'a> {
+    buf: &'a [u8],
+    index: usize,
+    shift: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            index: 0,
+            shift: 0,
+        }
+    }
+    fn is_empty(&self) -> bool {
+        self.index >= self.buf.len()
+    }
+    fn read(&mut self, bits: u32) -> u32 {
+        if self.shift > 8 {
+            self.index += 1;
+            self.shift -= 8;
+        }
+        let low = self.buf.get(self.index).copied().unwrap_or(0) as u32;
+        let high = self.buf.get(self.index + 1).copied().unwrap_or(0) as u32;
+        let ret = ((high << 8) | low) >> self.shift;
+        self.shift += bits;
+        ret & ((1 << bits) - 1)
+    }
+}
+
+/// Wrap raw PCM samples in RIFF/WAVE container
+fn pcm_to_wav(
+    pcm: &[u8],
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+) -> Vec<u8> {
+    let block_align = channels * bits_per_sample / 8;
+    let mut wav = Vec::with_capacity(pcm.len() + 44);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(pcm.len() as u32 + 36).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(pcm.len() as u32).to_le_bytes());
+    wav.extend_from_slice(pcm);
+    wav
+}
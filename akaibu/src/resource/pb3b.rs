@@ -1,5 +1,5 @@
 use super::{jbp1::jbp1_decompress, ResourceScheme, ResourceType};
-use crate::error::AkaibuError;
+use crate::{error::AkaibuError, options::Options};
 use anyhow::Context;
 use image::{buffer::ConvertBuffer, ImageBuffer, RgbaImage};
 use scroll::{Pread, LE};
@@ -20,7 +20,11 @@ struct Header {
 }
 
 impl ResourceScheme for Pb3bScheme {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType> {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
         file.read_to_end(&mut buf)?;
@@ -30,6 +34,7 @@ impl ResourceScheme for Pb3bScheme {
         &self,
         _file_path: &PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         self.from_bytes(buf)
     }
@@ -0,0 +1,134 @@
+use super::{gyu::GyuScheme, ResourceScheme, ResourceType};
+use crate::{
+    error::AkaibuError,
+    options::Options,
+    util::{find_sjis_strings, mt::MT19937},
+};
+use anyhow::Context;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, fs::File, io::Read, path::PathBuf};
+
+const KEYS_PATH: &str = "rld/keys.json";
+/// Key shared by all games, used for `def.rld`
+const DEF_KEY: &str = "def";
+const HEADER_SIZE: usize = 0x10;
+const KEY_TABLE_SIZE: usize = 0x100;
+/// Only beginning of file is encrypted
+const MAX_ENCRYPTED_WORDS: usize = 0x3FF0;
+
+static KEYS_TABLE: Lazy<HashMap<String, u32>> = Lazy::new(|| {
+    serde_json::from_slice(
+        &crate::Resources::get(KEYS_PATH)
+            .expect("Could not find file: rld/keys.json"),
+    )
+    .expect("Could not deserialize resource json")
+});
+
+/// `def.rld` uses key shared by all games while other scripts use game
+/// specific key, so only games from GYU game list with known key are listed
+#[derive(Debug, Clone)]
+pub(crate) enum RldScheme {
+    Shared,
+    Game(GyuScheme),
+}
+
+impl ResourceScheme for RldScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf, file_path)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf, file_path)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[RLD] {}",
+            match self {
+                Self::Shared => "Shared key (def.rld only)",
+                Self::Game(game) => game.get_title(),
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        let mut schemes: Vec<Box<dyn ResourceScheme>> =
+            vec![Box::new(Self::Shared)];
+        schemes.extend(
+            GyuScheme::get_games()
+                .into_iter()
+                .filter(|game| KEYS_TABLE.contains_key(game.get_key()))
+                .map(|game| {
+                    Box::new(Self::Game(game)) as Box<dyn ResourceScheme>
+                }),
+        );
+        schemes
+    }
+}
+
+impl RldScheme {
+    /// Decrypt script and dump its strings, one per line prefixed with
+    /// offset in decrypted script
+    fn from_bytes(
+        &self,
+        mut buf: Vec<u8>,
+        file_path: &PathBuf,
+    ) -> anyhow::Result<ResourceType> {
+        let is_def = file_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().eq_ignore_ascii_case(DEF_KEY))
+            .unwrap_or(false);
+        let key_name = match self {
+            _ if is_def => DEF_KEY,
+            Self::Shared => {
+                return Err(AkaibuError::Custom(
+                    "Only def.rld is encrypted with shared key".to_owned(),
+                )
+                .into())
+            }
+            Self::Game(game) => game.get_key(),
+        };
+        let key = *KEYS_TABLE.get(key_name).ok_or_else(|| {
+            AkaibuError::Custom(format!("RLD key {} is not known", key_name))
+        })?;
+        decrypt(
+            buf.get_mut(HEADER_SIZE..).context("Out of bounds access")?,
+            key,
+        );
+        let text = find_sjis_strings(&buf[HEADER_SIZE..]);
+        Ok(ResourceType::Script { data: buf, text })
+    }
+}
+
+/// XOR 32 bit words with table generated by MT19937 seeded with key
+fn decrypt(buf: &mut [u8], key: u32) {
+    let mut mt = MT19937::default();
+    mt.seed(key);
+    let key_table = (0..KEY_TABLE_SIZE)
+        .map(|_| mt.gen_u32())
+        .collect::<Vec<u32>>();
+    buf.chunks_exact_mut(4)
+        .take(MAX_ENCRYPTED_WORDS)
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            let word =
+                u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                    ^ key_table[i % KEY_TABLE_SIZE];
+            chunk.copy_from_slice(&word.to_le_bytes());
+        });
+}
@@ -1,9 +1,16 @@
 use super::{ResourceScheme, ResourceType};
-use crate::error::AkaibuError;
-use scroll::Pread;
+use crate::{error::AkaibuError, options::Options};
+use anyhow::Context;
+use image::{ImageBuffer, RgbaImage};
+use scroll::{Pread, LE};
 use std::{fs::File, io::Read};
 use tlg_rs::formats::{tlg0::Tlg0, tlg6::Tlg6};
 
+const TLG0_HEADER_SIZE: usize = 15;
+const TLG5_MAGIC: &[u8] = b"TLG5.0\0raw\x1a";
+const TLG5_HEADER_SIZE: usize = 24;
+const TLG5_WINDOW_SIZE: usize = 4096;
+
 #[derive(Debug, Clone)]
 pub(crate) enum TlgScheme {
     Universal,
@@ -13,6 +20,7 @@ impl ResourceScheme for TlgScheme {
     fn convert(
         &self,
         file_path: &std::path::PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<super::ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
@@ -24,6 +32,7 @@ impl ResourceScheme for TlgScheme {
         &self,
         _file_path: &std::path::PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         parse_tlg(buf)
     }
@@ -47,7 +56,11 @@ impl ResourceScheme for TlgScheme {
 
 fn parse_tlg(buf: Vec<u8>) -> anyhow::Result<ResourceType> {
     let image = match buf.pread::<u8>(3)? - 0x30 {
-        0 => Tlg0::from_bytes(&buf)?.to_rgba_image()?,
+        0 => match buf.get(TLG0_HEADER_SIZE..) {
+            Some(raw) if raw.starts_with(TLG5_MAGIC) => decode_tlg5(raw)?,
+            _ => Tlg0::from_bytes(&buf)?.to_rgba_image()?,
+        },
+        5 => decode_tlg5(&buf)?,
         6 => Tlg6::from_bytes(&buf)?.to_rgba_image()?,
         ver => {
             return Err(AkaibuError::Unimplemented(format!(
@@ -59,3 +72,136 @@ fn parse_tlg(buf: Vec<u8>) -> anyhow::Result<ResourceType> {
     };
     Ok(ResourceType::RgbaImage { image })
 }
+
+/// Decode TLG5 image. Each block of rows stores channels separately,
+/// optionally LZSS compressed with dictionary shared by all blocks.
+/// Pixels are stored as differences to left and upper neighbours with
+/// blue and red channels relative to green.
+fn decode_tlg5(buf: &[u8]) -> anyhow::Result<RgbaImage> {
+    let off = &mut TLG5_MAGIC.len();
+    let channel_count = buf.gread::<u8>(off)? as usize;
+    let width = buf.gread_with::<u32>(off, LE)? as usize;
+    let height = buf.gread_with::<u32>(off, LE)? as usize;
+    let block_height = buf.gread_with::<u32>(off, LE)? as usize;
+    if channel_count != 3 && channel_count != 4 {
+        return Err(AkaibuError::Unimplemented(format!(
+            "TLG5 with {} channels is not supported",
+            channel_count
+        ))
+        .into());
+    }
+    if block_height == 0 {
+        return Err(AkaibuError::Custom(
+            "Invalid TLG5 block height".to_owned(),
+        )
+        .into());
+    }
+    let block_count = (height + block_height - 1) / block_height;
+    *off = TLG5_HEADER_SIZE + block_count * 4;
+
+    let mut window = vec![0; TLG5_WINDOW_SIZE];
+    let mut window_off = 0;
+    let mut pixels = vec![0u8; width * height * 4];
+    for block in 0..block_count {
+        let block_y = block * block_height;
+        let rows = block_height.min(height - block_y);
+        let mut channels = Vec::with_capacity(channel_count);
+        for _ in 0..channel_count {
+            let mark = buf.gread::<u8>(off)?;
+            let size = buf.gread_with::<u32>(off, LE)? as usize;
+            let data =
+                buf.get(*off..*off + size).context("Out of bounds access")?;
+            *off += size;
+            channels.push(if mark == 0 {
+                tlg5_decompress(
+                    data,
+                    &mut window,
+                    &mut window_off,
+                    width * block_height,
+                )
+            } else {
+                data.to_vec()
+            });
+        }
+        for y in block_y..block_y + rows {
+            let row_off = (y - block_y) * width;
+            let mut acc = [0u8; 4];
+            for x in 0..width {
+                let mut c = [0u8; 4];
+                for (i, channel) in channels.iter().enumerate() {
+                    c[i] = *channel
+                        .get(row_off + x)
+                        .context("Out of bounds access")?;
+                }
+                c[0] = c[0].wrapping_add(c[1]);
+                c[2] = c[2].wrapping_add(c[1]);
+                let pixel = (y * width + x) * 4;
+                for i in 0..4 {
+                    acc[i] = acc[i].wrapping_add(c[i]);
+                    let above = if y > 0 {
+                        pixels[pixel - width * 4 + i]
+                    } else {
+                        0
+                    };
+                    pixels[pixel + i] = acc[i].wrapping_add(above);
+                }
+            }
+        }
+    }
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        if channel_count == 3 {
+            pixel[3] = 0xFF;
+        }
+    }
+    Ok(ImageBuffer::from_vec(width as u32, height as u32, pixels)
+        .context("Invalid image resolution")?)
+}
+
+fn tlg5_decompress(
+    src: &[u8],
+    window: &mut [u8],
+    window_off: &mut usize,
+    dest_size: usize,
+) -> Vec<u8> {
+    let mut dest = Vec::with_capacity(dest_size);
+    let mut src_off = 0;
+    let mut flags = 0u32;
+    while src_off < src.len() {
+        flags >>= 1;
+        if flags & 0x100 == 0 {
+            flags = src[src_off] as u32 | 0xFF00;
+            src_off += 1;
+        }
+        if src_off >= src.len() {
+            break;
+        }
+        if flags & 1 != 0 {
+            if src_off + 1 >= src.len() {
+                break;
+            }
+            let mut position = src[src_off] as usize
+                | ((src[src_off + 1] as usize & 0xF) << 8);
+            let mut length = (src[src_off + 1] as usize >> 4) + 3;
+            src_off += 2;
+            if length == 18 {
+                length += src.get(src_off).copied().unwrap_or(0) as usize;
+                src_off += 1;
+            }
+            for _ in 0..length {
+                let b = window[position];
+                dest.push(b);
+                window[*window_off] = b;
+                *window_off = (*window_off + 1) % TLG5_WINDOW_SIZE;
+                position = (position + 1) % TLG5_WINDOW_SIZE;
+            }
+        } else {
+            let b = src[src_off];
+            src_off += 1;
+            dest.push(b);
+            window[*window_off] = b;
+            *window_off = (*window_off + 1) % TLG5_WINDOW_SIZE;
+        }
+    }
+    dest
+}
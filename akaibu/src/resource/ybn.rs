@@ -0,0 +1,179 @@
+use super::{ResourceScheme, ResourceType};
+use crate::{error::AkaibuError, options::Options};
+use encoding_rs::SHIFT_JIS;
+use scroll::{Pread, LE};
+use std::{fs::File, io::Read, path::PathBuf};
+
+const HEADER_SIZE: usize = 0x20;
+const ARGUMENT_SIZE: usize = 12;
+/// Expression token holding string literal
+const TOKEN_STRING: u8 = b'M';
+
+#[derive(Debug, Clone)]
+pub(crate) enum YbnScheme {
+    Universal,
+}
+
+#[derive(Debug, Pread)]
+struct YbnHeader {
+    magic: [u8; 4],
+    version: u32,
+    instruction_count: u32,
+    code_size: u32,
+    argument_size: u32,
+    resource_size: u32,
+    offset_size: u32,
+    padding: u32,
+}
+
+#[derive(Debug, Pread)]
+struct YbnArgument {
+    value: u16,
+    arg_type: u16,
+    size: u32,
+    offset: u32,
+}
+
+impl ResourceScheme for YbnScheme {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        let mut buf = Vec::with_capacity(1 << 20);
+        let mut file = File::open(file_path)?;
+        file.read_to_end(&mut buf)?;
+        self.from_bytes(buf)
+    }
+
+    fn convert_from_bytes(
+        &self,
+        _file_path: &PathBuf,
+        buf: Vec<u8>,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
+        self.from_bytes(buf)
+    }
+
+    fn get_name(&self) -> String {
+        format!(
+            "[YBN] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+
+    fn get_schemes() -> Vec<Box<dyn ResourceScheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+impl YbnScheme {
+    /// Dump string literals and raw text arguments, one per line prefixed
+    /// with offset in resource section
+    fn from_bytes(&self, mut buf: Vec<u8>) -> anyhow::Result<ResourceType> {
+        let header = buf.pread_with::<YbnHeader>(0, LE)?;
+        log::debug!("Header: {:?}", header);
+        let code_start = HEADER_SIZE;
+        let argument_start = code_start + header.code_size as usize;
+        let resource_start = argument_start + header.argument_size as usize;
+        let offset_start = resource_start + header.resource_size as usize;
+        let offset_end = offset_start + header.offset_size as usize;
+        if offset_end != buf.len() {
+            return Err(AkaibuError::Unimplemented(format!(
+                "Unsupported YBN version {}",
+                header.version
+            ))
+            .into());
+        }
+
+        let key = Self::find_key(&buf[argument_start..resource_start])?;
+        log::debug!("Key: {:08X}", key);
+        for section in [
+            code_start..argument_start,
+            argument_start..resource_start,
+            resource_start..offset_start,
+            offset_start..offset_end,
+        ]
+        .iter()
+        {
+            xor(&mut buf[section.clone()], key);
+        }
+
+        let resources = &buf[resource_start..offset_start];
+        let mut text = String::new();
+        for argument in buf[argument_start..resource_start]
+            .chunks_exact(ARGUMENT_SIZE)
+            .map(|c| c.pread_with::<YbnArgument>(0, LE))
+        {
+            let argument = argument?;
+            let start = argument.offset as usize;
+            let data =
+                match resources.get(start..start + argument.size as usize) {
+                    Some(data) if !data.is_empty() => data,
+                    _ => continue,
+                };
+            if let Some(line) = Self::parse_text(data) {
+                text.push_str(&format!(
+                    "{:08X}\t{}\n",
+                    start,
+                    line.replace("\n", "\\n")
+                ));
+            }
+        }
+        Ok(ResourceType::Text(text))
+    }
+    /// Resource offset of first argument is always 0 so its encrypted
+    /// value is the key. Script without arguments has no text and is left
+    /// as it is.
+    fn find_key(arguments: &[u8]) -> anyhow::Result<u32> {
+        if arguments.is_empty() {
+            log::info!("YBN has no arguments, treating it as not encrypted");
+            return Ok(0);
+        }
+        arguments.pread_with::<u32>(8, LE).map_err(|_| {
+            AkaibuError::Custom(format!(
+                "YBN argument section of {} bytes is too short to hold key",
+                arguments.len()
+            ))
+            .into()
+        })
+    }
+    /// String literal expression is `M` token followed by length and
+    /// quoted string, message arguments are stored as plain text
+    fn parse_text(data: &[u8]) -> Option<String> {
+        let raw = match data {
+            [TOKEN_STRING, ..] => {
+                let len = data.pread_with::<u16>(1, LE).ok()? as usize;
+                let literal = data.get(3..3 + len)?;
+                match literal {
+                    [b'"', .., b'"'] | [b'\'', .., b'\''] => {
+                        &literal[1..literal.len() - 1]
+                    }
+                    _ => return None,
+                }
+            }
+            _ => data,
+        };
+        let (text, _, had_errors) = SHIFT_JIS.decode(raw);
+        if had_errors
+            || text.chars().any(|c| c.is_control() && c != '\n')
+            || text.chars().all(|c| c.is_ascii())
+        {
+            None
+        } else {
+            Some(text.into_owned())
+        }
+    }
+}
+
+fn xor(buf: &mut [u8], key: u32) {
+    let key = key.to_le_bytes();
+    buf.iter_mut()
+        .enumerate()
+        .for_each(|(i, b)| *b ^= key[i % 4]);
+}
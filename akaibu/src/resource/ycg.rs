@@ -1,4 +1,4 @@
-use crate::{error::AkaibuError, util::zlib_decompress};
+use crate::{error::AkaibuError, options::Options, util::zlib_decompress};
 use anyhow::Context;
 use image::{buffer::ConvertBuffer, ImageBuffer};
 use scroll::{Pread, LE};
@@ -12,7 +12,11 @@ pub(crate) enum YcgScheme {
 }
 
 impl ResourceScheme for YcgScheme {
-    fn convert(&self, file_path: &PathBuf) -> anyhow::Result<ResourceType> {
+    fn convert(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<ResourceType> {
         let mut buf = Vec::with_capacity(1 << 20);
         let mut file = File::open(file_path)?;
         file.read_to_end(&mut buf)?;
@@ -22,6 +26,7 @@ impl ResourceScheme for YcgScheme {
         &self,
         _file_path: &PathBuf,
         buf: Vec<u8>,
+        _options: &Options,
     ) -> anyhow::Result<ResourceType> {
         self.from_bytes(buf)
     }
@@ -1,17 +1,119 @@
 use super::Scheme;
 use crate::{
     archive,
-    util::{crc64, zlib_decompress},
+    error::AkaibuError,
+    options::Options,
+    resource::acv1_script::dump_script_text,
+    util::{adler32, crc64},
 };
 use anyhow::Context;
 use bytes::{Bytes, BytesMut};
 use encoding_rs::SHIFT_JIS;
 use positioned_io::{RandomAccessFile, ReadAt};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{
+    IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
 use scroll::{ctx, Pread, LE};
-use std::{collections::HashMap, fs::File, io::Write, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::Write,
+    path::PathBuf,
+};
 
 const MASTER_KEY: u32 = 0x8B6A4E5F;
+const FILE_NAMES_PATH: &str = "acv1/all_file_names.txt";
+const ENTRY_SIZE: usize = 21;
+/// Max amount of file names generated from single name pattern
+const MAX_GUESS_CANDIDATES: u64 = 1 << 24;
+/// Amount of numbers generated for `%d` placeholder without width
+const DEFAULT_PLACEHOLDER_RANGE: u64 = 1000;
+/// Zlib headers (CMF, FLG) for all compression levels with 32K window size
+const ZLIB_HEADERS: [[u8; 2]; 4] =
+    [[0x78, 0x01], [0x78, 0x5E], [0x78, 0x9C], [0x78, 0xDA]];
+/// Max amount of script entries used to validate recovered script key
+const SCRIPT_KEY_VALIDATION_ENTRIES: usize = 4;
+/// Given to script entries without known file name so they can be
+/// converted later
+const SCRIPT_EXTENSION: &str = "acvs";
+
+/// Options shared by all ACV1 schemes
+#[derive(Debug, Clone, Default)]
+pub struct Acv1Options {
+    /// Extract entries without known file name under their crc64 hash
+    pub extract_unnamed: bool,
+    /// Additional file name lists with one name per line (UTF-8 or SHIFT_JIS)
+    pub name_lists: Vec<PathBuf>,
+    /// Patterns used to guess unknown file names e.g. `bg_%03d.png`
+    pub name_patterns: Vec<String>,
+    /// Write text dump of each script next to extracted script
+    pub dump_script_text: bool,
+}
+
+/// File name coverage of ACV1 archive
+#[derive(Debug, Clone, Default)]
+pub struct Acv1Coverage {
+    pub total: usize,
+    /// Entries with known file name
+    pub named: usize,
+    /// Entries without known file name extracted under their crc64 hash
+    pub hashed: usize,
+    /// Entries without known file name that are not extracted
+    pub unnamed: usize,
+    /// File names found by guessing from name patterns
+    pub guessed: Vec<String>,
+}
+
+impl Acv1Coverage {
+    fn new(entries: &[Acv1Entry], guessed: Vec<String>) -> Self {
+        entries.iter().fold(
+            Self {
+                total: entries.len(),
+                guessed,
+                ..Default::default()
+            },
+            |mut coverage, entry| {
+                if entry.named {
+                    coverage.named += 1;
+                } else if entry.extractable {
+                    coverage.hashed += 1;
+                } else {
+                    coverage.unnamed += 1;
+                }
+                coverage
+            },
+        )
+    }
+}
+
+impl fmt::Display for Acv1Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries, {} named ({:.2}%), {} hashed, {} unnamed, {} guessed",
+            self.total,
+            self.named,
+            if self.total == 0 {
+                100.0
+            } else {
+                self.named as f64 * 100.0 / self.total as f64
+            },
+            self.hashed,
+            self.unnamed,
+            self.guessed.len()
+        )
+    }
+}
+
+/// Get file name coverage of ACV1 archive using current options
+pub fn coverage_report(
+    file_path: &PathBuf,
+    options: &Acv1Options,
+) -> anyhow::Result<Acv1Coverage> {
+    let (_, archive, guessed) = open_archive(file_path, options)?;
+    Ok(Acv1Coverage::new(&archive.file_entries, guessed))
+}
 
 #[derive(Debug, Clone)]
 pub enum Acv1Scheme {
@@ -19,34 +121,33 @@ pub enum Acv1Scheme {
     Shukugar2,
     Shukugar3,
     HanaHime,
+    Generic,
 }
 
 impl Scheme for Acv1Scheme {
     fn extract(
         &self,
         file_path: &PathBuf,
+        options: &Options,
     ) -> anyhow::Result<(
         Box<dyn archive::Archive + Sync>,
         archive::NavigableDirectory,
     )> {
-        let file_names = crate::Resources::get("acv1/all_file_names.txt")
-            .context("Could not get resouce")?;
-        let (sjis_file_names, _encoding_used, _any_errors) =
-            SHIFT_JIS.decode(&file_names);
-
-        let mut hashes = HashMap::new();
-        sjis_file_names.lines().for_each(|l| {
-            hashes.insert(crc64(&SHIFT_JIS.encode(&l).0), l);
-        });
-        let mut buf = vec![0; 4];
-        let file = RandomAccessFile::open(file_path)?;
-        file.read_exact_at(4, &mut buf)?;
-        let entries_count = buf.pread_with::<u32>(0, LE)? ^ MASTER_KEY;
-        let mut buf = vec![0; 4 + entries_count as usize * 21];
-        file.read_exact_at(8, &mut buf)?;
+        let (file, archive, guessed) = open_archive(file_path, &options.acv1)?;
+        log::info!(
+            "File name coverage: {}",
+            Acv1Coverage::new(&archive.file_entries, guessed)
+        );
 
-        let archive = buf.pread_with::<Acv1>(0, (entries_count, &hashes))?;
-        log::debug!("Archive: {:?}", archive);
+        let script_key = match self.get_script_key() {
+            Some(script_key) => script_key,
+            None => {
+                let script_key =
+                    recover_script_key(&file, &archive.file_entries)?;
+                log::info!("Recovered script key: {:#x}", script_key);
+                script_key
+            }
+        };
 
         let root_dir = Acv1Archive::new_root_dir(&archive.file_entries);
         let navigable_dir = archive::NavigableDirectory::new(root_dir);
@@ -54,7 +155,8 @@ impl Scheme for Acv1Scheme {
             Box::new(Acv1Archive {
                 file,
                 archive,
-                script_key: self.get_script_key(),
+                script_key,
+                dump_script_text: options.acv1.dump_script_text,
             }),
             navigable_dir,
         ))
@@ -73,6 +175,7 @@ impl Scheme for Acv1Scheme {
                     "Shukusei no Girlfriend 3 -the destiny star of girlfriend-"
                 }
                 Self::HanaHime => "Hana Hime * Absolute!",
+                Self::Generic => "Generic (recover script key from archive)",
             }
         )
     }
@@ -85,26 +188,308 @@ impl Scheme for Acv1Scheme {
             Box::new(Acv1Scheme::Shukugar2),
             Box::new(Acv1Scheme::Shukugar3),
             Box::new(Acv1Scheme::HanaHime),
+            Box::new(Acv1Scheme::Generic),
         ]
     }
 }
 
 impl Acv1Scheme {
-    fn get_script_key(&self) -> u32 {
+    fn get_script_key(&self) -> Option<u32> {
         match self {
-            Self::Shukugar1 => 0x9d0be0fa,
-            Self::Shukugar2 => 0xcf762ea8,
-            Self::Shukugar3 => 0x3548751d,
-            Self::HanaHime => 0x30bc61c8,
+            Self::Shukugar1 => Some(0x9d0be0fa),
+            Self::Shukugar2 => Some(0xcf762ea8),
+            Self::Shukugar3 => Some(0x3548751d),
+            Self::HanaHime => Some(0x30bc61c8),
+            Self::Generic => None,
         }
     }
 }
 
+/// Read archive index and resolve entry names.
+/// Returns also file names that were found by guessing from name patterns.
+fn open_archive(
+    file_path: &PathBuf,
+    options: &Acv1Options,
+) -> anyhow::Result<(RandomAccessFile, Acv1, Vec<String>)> {
+    let mut buf = vec![0; 4];
+    let file = RandomAccessFile::open(file_path)?;
+    file.read_exact_at(4, &mut buf)?;
+    let entries_count = buf.pread_with::<u32>(0, LE)? ^ MASTER_KEY;
+    let mut buf = vec![0; 4 + entries_count as usize * ENTRY_SIZE];
+    file.read_exact_at(8, &mut buf)?;
+
+    let mut hashes = load_file_names(&options.name_lists)?;
+    let unknown = buf
+        .chunks_exact(ENTRY_SIZE)
+        .map(|c| c.pread_with::<u64>(0, LE))
+        .filter(|crc| match crc {
+            Ok(crc) => !hashes.contains_key(crc),
+            Err(_) => true,
+        })
+        .collect::<Result<HashSet<u64>, _>>()?;
+    let guessed = guess_file_names(&options.name_patterns, &unknown)?;
+    guessed.iter().for_each(|name| {
+        log::info!("Guessed file name: {}", name);
+        hashes.insert(crc64(&SHIFT_JIS.encode(name).0), name.clone());
+    });
+
+    let archive = buf.pread_with::<Acv1>(
+        0,
+        (entries_count, &hashes, options.extract_unnamed),
+    )?;
+    log::debug!("Archive: {:?}", archive);
+    Ok((file, archive, guessed))
+}
+
+/// Load embedded file names and additional name lists into crc64 lookup table
+fn load_file_names(
+    name_lists: &[PathBuf],
+) -> anyhow::Result<HashMap<u64, String>> {
+    let file_names = crate::Resources::get(FILE_NAMES_PATH)
+        .context("Could not get resouce")?;
+    let (sjis_file_names, _encoding_used, _any_errors) =
+        SHIFT_JIS.decode(&file_names);
+
+    let mut hashes = HashMap::new();
+    sjis_file_names.lines().for_each(|l| {
+        hashes.insert(crc64(&SHIFT_JIS.encode(&l).0), l.to_string());
+    });
+    for name_list in name_lists {
+        let buf = std::fs::read(name_list)?;
+        let names = match std::str::from_utf8(&buf) {
+            Ok(names) => names.to_string(),
+            Err(_) => SHIFT_JIS.decode(&buf).0.to_string(),
+        };
+        let count = hashes.len();
+        names
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .for_each(|l| {
+                hashes.insert(crc64(&SHIFT_JIS.encode(&l).0), l.to_string());
+            });
+        log::debug!(
+            "Loaded {} new file names from {:?}",
+            hashes.len() - count,
+            name_list
+        );
+    }
+    Ok(hashes)
+}
+
+#[derive(Debug)]
+enum PatternPart {
+    Literal(String),
+    Number { width: usize, range: u64 },
+}
+
+/// Parse printf like pattern. Supported placeholders are `%d`, `%Nd`, `%0Nd` and `%%`
+fn parse_pattern(pattern: &str) -> anyhow::Result<Vec<PatternPart>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            literal.push('%');
+            continue;
+        }
+        let mut width = String::new();
+        while let Some(d) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            width.push(*d);
+            chars.next();
+        }
+        if chars.next() != Some('d') {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid placeholder in file name pattern: {}",
+                pattern
+            ))
+            .into());
+        }
+        if !literal.is_empty() {
+            parts.push(PatternPart::Literal(std::mem::take(&mut literal)));
+        }
+        parts.push(if width.is_empty() {
+            PatternPart::Number {
+                width: 0,
+                range: DEFAULT_PLACEHOLDER_RANGE,
+            }
+        } else {
+            let width = width.parse::<usize>()?;
+            PatternPart::Number {
+                width,
+                range: 10_u64.checked_pow(width as u32).unwrap_or(u64::MAX),
+            }
+        });
+    }
+    if !literal.is_empty() {
+        parts.push(PatternPart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+/// Generate file names from patterns and return ones matching unknown hashes
+fn guess_file_names(
+    patterns: &[String],
+    unknown: &HashSet<u64>,
+) -> anyhow::Result<Vec<String>> {
+    let mut guessed = Vec::new();
+    if unknown.is_empty() {
+        return Ok(guessed);
+    }
+    for pattern in patterns {
+        let parts = parse_pattern(pattern)?;
+        let candidates_count = parts
+            .iter()
+            .try_fold(1_u64, |count, part| match part {
+                PatternPart::Literal(_) => Some(count),
+                PatternPart::Number { range, .. } => count.checked_mul(*range),
+            })
+            .filter(|count| *count <= MAX_GUESS_CANDIDATES)
+            .ok_or_else(|| {
+                AkaibuError::Custom(format!(
+                    "File name pattern generates too many candidates: {}",
+                    pattern
+                ))
+            })?;
+        log::debug!(
+            "Checking {} candidates for pattern: {}",
+            candidates_count,
+            pattern
+        );
+        guessed.extend(
+            (0..candidates_count)
+                .into_par_iter()
+                .map(|mut n| {
+                    parts.iter().rev().fold(Vec::new(), |mut v, part| {
+                        match part {
+                            PatternPart::Literal(s) => v.push(s.clone()),
+                            PatternPart::Number { width, range } => {
+                                v.push(format!(
+                                    "{:0width$}",
+                                    n % range,
+                                    width = width
+                                ));
+                                n /= range;
+                            }
+                        }
+                        v
+                    })
+                })
+                .map(|v| v.into_iter().rev().collect::<String>())
+                .filter(|name| {
+                    unknown.contains(&crc64(&SHIFT_JIS.encode(name).0))
+                })
+                .collect::<Vec<String>>(),
+        );
+    }
+    Ok(guessed)
+}
+
+/// Recover script key from encrypted script entries.
+/// Scripts are xored with `crc64 as u32 ^ script_key` and then zlib compressed,
+/// so lower 16 bits of key can be derived from known zlib header and upper 16 bits
+/// are brute-forced and validated by successful decompression.
+fn recover_script_key(
+    file: &RandomAccessFile,
+    entries: &[Acv1Entry],
+) -> anyhow::Result<u32> {
+    let mut scripts = entries
+        .iter()
+        .filter(|e| e.flags == 6 && e.file_size >= 4)
+        .collect::<Vec<&Acv1Entry>>();
+    scripts.sort_by_key(|e| e.file_size);
+    let (smallest, rest) = scripts.split_first().ok_or_else(|| {
+        AkaibuError::Custom(
+            "Archive does not contain any script entries".to_owned(),
+        )
+    })?;
+    let mut buf = vec![0; smallest.file_size as usize];
+    file.read_exact_at(smallest.file_offset as u64, &mut buf)?;
+
+    let candidates = ZLIB_HEADERS
+        .iter()
+        .map(|header| {
+            (buf[0] ^ header[0]) as u32 | ((buf[1] ^ header[1]) as u32) << 8
+        })
+        .flat_map(|low| {
+            (0..=0xFFFF_u32)
+                .into_par_iter()
+                .map(move |high| low | high << 16)
+                .filter(|xor_key| {
+                    let mut buf = buf.clone();
+                    xor_with_key(&mut buf, *xor_key);
+                    decompress_script(&buf).is_ok()
+                })
+                .map(|xor_key| xor_key ^ smallest.crc64 as u32)
+                .collect::<Vec<u32>>()
+        })
+        .collect::<Vec<u32>>();
+    log::debug!("Script key candidates: {:X?}", candidates);
+    if rest.is_empty() && candidates.len() > 1 {
+        return Err(AkaibuError::Custom(format!(
+            "Could not recover script key, {} candidates match the only script",
+            candidates.len()
+        ))
+        .into());
+    }
+
+    candidates
+        .into_iter()
+        .find(|script_key| {
+            rest.iter()
+                .take(SCRIPT_KEY_VALIDATION_ENTRIES)
+                .all(|e| e.dump_script(file, *script_key).is_ok())
+        })
+        .ok_or_else(|| {
+            AkaibuError::Custom("Could not recover script key".to_owned())
+                .into()
+        })
+}
+
+/// Inflate zlib compressed script and verify Adler-32 checksum stored after
+/// deflate stream, so wrongly decrypted data which happens to inflate is
+/// rejected
+fn decompress_script(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    let stream = buf.get(2..).context("Out of bounds access")?;
+    let mut decoder = DeflateDecoder::new(stream);
+    let mut ret = Vec::with_capacity(buf.len());
+    decoder.read_to_end(&mut ret)?;
+    let trailer_offset = 2 + decoder.total_in() as usize;
+    let checksum = buf
+        .pread_with::<u32>(trailer_offset, scroll::BE)
+        .context("Missing Adler-32 checksum")?;
+    if checksum != adler32(&ret) {
+        return Err(AkaibuError::Custom(
+            "Adler-32 checksum mismatch".to_owned(),
+        )
+        .into());
+    }
+    Ok(ret)
+}
+
+fn xor_with_key(buf: &mut [u8], xor_key: u32) {
+    buf.chunks_exact_mut(4).for_each(|c| {
+        c[0] ^= xor_key as u8;
+        c[1] ^= (xor_key >> 8) as u8;
+        c[2] ^= (xor_key >> 16) as u8;
+        c[3] ^= (xor_key >> 24) as u8;
+    });
+}
+
 #[derive(Debug)]
 struct Acv1Archive {
     file: RandomAccessFile,
     script_key: u32,
     archive: Acv1,
+    dump_script_text: bool,
 }
 
 impl archive::Archive for Acv1Archive {
@@ -122,23 +507,33 @@ impl archive::Archive for Acv1Archive {
     }
 
     fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
-        self.archive.file_entries.par_iter().try_for_each(|entry| {
-            let buf = self.extract(entry)?;
-            let mut output_file_name = PathBuf::from(output_path);
-            output_file_name.push(&entry.full_path);
-            std::fs::create_dir_all(
-                &output_file_name
-                    .parent()
-                    .context("Could not get parent directory")?,
-            )?;
-            log::debug!(
-                "Extracting resource: {:?} {:X?}",
-                output_file_name,
-                entry
-            );
-            File::create(output_file_name)?.write_all(&buf)?;
-            Ok(())
-        })
+        self.archive
+            .file_entries
+            .par_iter()
+            .filter(|entry| entry.extractable)
+            .try_for_each(|entry| {
+                let buf = self.extract(entry)?;
+                let mut output_file_name = PathBuf::from(output_path);
+                output_file_name.push(&entry.full_path);
+                std::fs::create_dir_all(
+                    &output_file_name
+                        .parent()
+                        .context("Could not get parent directory")?,
+                )?;
+                log::debug!(
+                    "Extracting resource: {:?} {:X?}",
+                    output_file_name,
+                    entry
+                );
+                File::create(&output_file_name)?.write_all(&buf)?;
+                if entry.flags == 6 && self.dump_script_text {
+                    let mut text_file_name = output_file_name.into_os_string();
+                    text_file_name.push(".txt");
+                    File::create(text_file_name)?
+                        .write_all(dump_script_text(&buf).as_bytes())?;
+                }
+                Ok(())
+            })
     }
 }
 
@@ -184,17 +579,21 @@ struct Acv1 {
     file_entries: Vec<Acv1Entry>,
 }
 
-impl<'a> ctx::TryFromCtx<'a, (u32, &HashMap<u64, &str>)> for Acv1 {
+impl<'a> ctx::TryFromCtx<'a, (u32, &HashMap<u64, String>, bool)> for Acv1 {
     type Error = anyhow::Error;
     #[inline]
     fn try_from_ctx(
         buf: &'a [u8],
-        (entry_count, hashes): (u32, &HashMap<u64, &str>),
+        (entry_count, hashes, extract_unnamed): (
+            u32,
+            &HashMap<u64, String>,
+            bool,
+        ),
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
         let mut file_entries = Vec::with_capacity(entry_count as usize);
         for _ in 0..entry_count {
-            file_entries.push(buf.gread_with(off, hashes)?)
+            file_entries.push(buf.gread_with(off, (hashes, extract_unnamed))?)
         }
         Ok((Acv1 { file_entries }, 4))
     }
@@ -208,18 +607,21 @@ struct Acv1Entry {
     file_size: u32,
     uncompressed_file_size: u32,
     full_path: PathBuf,
-    /// File is not extractable when:
+    /// File name was found for its crc64
+    named: bool,
+    /// File is not extractable when there is no file name for its crc64 and:
     /// - its is not a script file
-    /// - there is no file name for its crc64 in acv1/all_file_names.txt file
+    /// - extraction of unnamed entries is disabled or file offset and size
+    ///   are encrypted with file name
     extractable: bool,
 }
 
-impl<'a> ctx::TryFromCtx<'a, &HashMap<u64, &str>> for Acv1Entry {
+impl<'a> ctx::TryFromCtx<'a, (&HashMap<u64, String>, bool)> for Acv1Entry {
     type Error = anyhow::Error;
     #[inline]
     fn try_from_ctx(
         buf: &'a [u8],
-        hashes: &HashMap<u64, &str>,
+        (hashes, extract_unnamed): (&HashMap<u64, String>, bool),
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
         let crc64 = buf.gread_with::<u64>(off, LE)?;
@@ -231,6 +633,7 @@ impl<'a> ctx::TryFromCtx<'a, &HashMap<u64, &str>> for Acv1Entry {
         let mut file_size = buf.gread_with::<u32>(off, LE)? ^ xor_key;
         let mut uncompressed_file_size =
             buf.gread_with::<u32>(off, LE)? ^ xor_key;
+        let named = hashes.contains_key(&crc64);
         let mut extractable = true;
 
         let full_path = PathBuf::from(if let Some(v) = hashes.get(&crc64) {
@@ -251,7 +654,9 @@ impl<'a> ctx::TryFromCtx<'a, &HashMap<u64, &str>> for Acv1Entry {
                     as u32;
             }
             file_name
-        } else if flags & 4 >= 1 {
+        } else if flags == 6 {
+            format!("{:X}.{}", crc64, SCRIPT_EXTENSION)
+        } else if flags & 4 >= 1 || (extract_unnamed && flags & 2 != 0) {
             format!("{:X}", crc64)
         } else {
             extractable = false;
@@ -265,9 +670,10 @@ impl<'a> ctx::TryFromCtx<'a, &HashMap<u64, &str>> for Acv1Entry {
                 file_size,
                 uncompressed_file_size,
                 full_path,
+                named,
                 extractable,
             },
-            21,
+            ENTRY_SIZE,
         ))
     }
 }
@@ -302,14 +708,8 @@ impl Acv1Entry {
             }
             return Ok(buf.freeze());
         }
-        let xor_key = self.crc64 as u32;
-        buf.chunks_exact_mut(4).for_each(|c| {
-            c[0] ^= xor_key as u8;
-            c[1] ^= (xor_key >> 8) as u8;
-            c[2] ^= (xor_key >> 16) as u8;
-            c[3] ^= (xor_key >> 24) as u8;
-        });
-        Ok(Bytes::from(zlib_decompress(&buf)?))
+        xor_with_key(&mut buf, self.crc64 as u32);
+        Ok(Bytes::from(decompress_script(&buf)?))
     }
     fn dump_script(
         &self,
@@ -320,14 +720,8 @@ impl Acv1Entry {
         buf.resize(self.file_size as usize, 0);
         file.read_exact_at(self.file_offset as u64, &mut buf)?;
 
-        let xor_key = self.crc64 as u32 ^ script_key;
-        buf.chunks_exact_mut(4).for_each(|c| {
-            c[0] ^= xor_key as u8;
-            c[1] ^= (xor_key >> 8) as u8;
-            c[2] ^= (xor_key >> 16) as u8;
-            c[3] ^= (xor_key >> 24) as u8;
-        });
+        xor_with_key(&mut buf, self.crc64 as u32 ^ script_key);
 
-        Ok(Bytes::from(zlib_decompress(&buf)?))
+        Ok(Bytes::from(decompress_script(&buf)?))
     }
 }
@@ -1,5 +1,5 @@
 use super::Scheme;
-use crate::archive;
+use crate::{archive, options::Options};
 use anyhow::Context;
 use bytes::Bytes;
 use bytes::BytesMut;
@@ -17,7 +17,20 @@ use std::path::PathBuf;
 
 const BURIKO_ENTRY_SIZE: usize = 0x80;
 const BURIKO_ENTRY_NAME_SIZE: usize = 0x60;
+const PACKFILE_MAGIC: &[u8] = b"PackFile    ";
+const PACKFILE_ENTRY_SIZE: usize = 0x20;
+const PACKFILE_ENTRY_NAME_SIZE: usize = 0x10;
 const SOUND_FILE_MAGIC: &[u8] = b"bw  ";
+const DSC_MAGIC: &[u8] = b"DSC FORMAT 1.00\0";
+const DSC_HEADER_SIZE: usize = 0x20;
+const DSC_CODE_COUNT: usize = 512;
+
+/// Options used by BURIKO scheme
+#[derive(Debug, Clone, Default)]
+pub struct BurikoOptions {
+    /// Do not decompress `DSC FORMAT 1.00` entries
+    pub keep_raw: bool,
+}
 
 #[derive(Debug, Clone)]
 pub enum BurikoScheme {
@@ -28,6 +41,7 @@ impl Scheme for BurikoScheme {
     fn extract(
         &self,
         file_path: &std::path::PathBuf,
+        options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         crate::archive::NavigableDirectory,
@@ -39,14 +53,21 @@ impl Scheme for BurikoScheme {
         let header = buf.pread::<BurikoHeader>(0)?;
         log::debug!("Header: {:#?}", header);
 
-        let mut buf = vec![0; header.entry_count as usize * BURIKO_ENTRY_SIZE];
+        let mut buf = vec![0; header.entry_count as usize * header.entry_size];
         file.read_exact_at(16, &mut buf)?;
         let archive = buf.pread_with::<Buriko>(0, header)?;
         log::debug!("Archive: {:#?}", archive);
 
         let root_dir = BurikoArchive::new_root_dir(&archive.file_entries);
         let navigable_dir = archive::NavigableDirectory::new(root_dir);
-        Ok((Box::new(BurikoArchive { file, archive }), navigable_dir))
+        Ok((
+            Box::new(BurikoArchive {
+                file,
+                archive,
+                keep_raw: options.buriko.keep_raw,
+            }),
+            navigable_dir,
+        ))
     }
 
     fn get_name(&self) -> String {
@@ -70,6 +91,7 @@ impl Scheme for BurikoScheme {
 struct BurikoArchive {
     file: RandomAccessFile,
     archive: Buriko,
+    keep_raw: bool,
 }
 
 impl archive::Archive for BurikoArchive {
@@ -141,6 +163,9 @@ impl BurikoArchive {
             self.archive.header.file_contents_offset + entry.file_offset as u64,
             &mut buf,
         )?;
+        if buf.starts_with(DSC_MAGIC) && !self.keep_raw {
+            return Ok(Bytes::from(dsc_decompress(&buf)?));
+        }
         if buf.get(4..8).context("Out of bounds access")? == SOUND_FILE_MAGIC {
             buf = buf.split_off(0x40);
         }
@@ -148,6 +173,152 @@ impl BurikoArchive {
     }
 }
 
+/// Decompress `DSC FORMAT 1.00` data. Huffman code lengths are
+/// obfuscated with key stream seeded from header.
+fn dsc_decompress(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let magic = (buf.pread_with::<u16>(0, LE)? as u32) << 16;
+    let mut key = buf.pread_with::<u32>(0x10, LE)?;
+    let dest_size = buf.pread_with::<u32>(0x14, LE)? as usize;
+    let dec_count = buf.pread_with::<u32>(0x18, LE)?;
+    let code_lengths = buf
+        .get(DSC_HEADER_SIZE..DSC_HEADER_SIZE + DSC_CODE_COUNT)
+        .context("Out of bounds access")?;
+
+    let mut codes = Vec::with_capacity(DSC_CODE_COUNT);
+    for (code, src) in code_lengths.iter().enumerate() {
+        let depth = src.wrapping_sub(dsc_update_key(&mut key, magic));
+        if depth != 0 {
+            codes.push((depth, code as u16));
+        }
+    }
+    codes.sort_unstable();
+    let tree = dsc_create_tree(&codes);
+
+    let mut reader =
+        DscBitReader::new(&buf[DSC_HEADER_SIZE + DSC_CODE_COUNT..]);
+    let mut dest = Vec::with_capacity(dest_size);
+    for _ in 0..dec_count {
+        let mut node_index = 0;
+        loop {
+            let node = tree.get(node_index).context("Invalid DSC tree")?;
+            node_index = match node {
+                DscNode::Parent { left, right } => {
+                    if reader.read(1)? == 0 {
+                        *left
+                    } else {
+                        *right
+                    }
+                }
+                DscNode::Leaf(_) => break,
+            };
+        }
+        match tree[node_index] {
+            DscNode::Leaf(code) if code >= 256 => {
+                let offset = reader.read(12)? as usize + 2;
+                let count = (code & 0xFF) as usize + 2;
+                let mut back_index = dest
+                    .len()
+                    .checked_sub(offset)
+                    .context("Invalid back reference")?;
+                for _ in 0..count {
+                    dest.push(dest[back_index]);
+                    back_index += 1;
+                }
+            }
+            DscNode::Leaf(code) => dest.push(code as u8),
+            DscNode::Parent { .. } => unreachable!(),
+        }
+    }
+    dest.truncate(dest_size);
+    Ok(dest)
+}
+
+fn dsc_update_key(key: &mut u32, magic: u32) -> u8 {
+    let v0 = 20021u32.wrapping_mul(*key & 0xFFFF);
+    let mut v1 = magic | (*key >> 16);
+    v1 = v1.wrapping_mul(20021).wrapping_add(key.wrapping_mul(346));
+    v1 = (v1.wrapping_add(v0 >> 16)) & 0xFFFF;
+    *key = (v1 << 16).wrapping_add(v0 & 0xFFFF).wrapping_add(1);
+    v1 as u8
+}
+
+/// Build canonical Huffman tree from (depth, code) pairs sorted by depth
+fn dsc_create_tree(codes: &[(u8, u16)]) -> Vec<DscNode> {
+    let mut tree = vec![DscNode::Leaf(0); DSC_CODE_COUNT * 2];
+    let mut level = vec![0];
+    let mut next_node_index = 1;
+    let mut depth = 0;
+    let mut n = 0;
+    while n < codes.len() && !level.is_empty() {
+        let mut existing = 0;
+        while n < codes.len() && codes[n].0 == depth {
+            if let Some(index) = level.get(existing) {
+                tree[*index] = DscNode::Leaf(codes[n].1);
+            }
+            existing += 1;
+            n += 1;
+        }
+        let mut next_level = Vec::new();
+        for index in level.iter().skip(existing) {
+            if next_node_index + 1 >= tree.len() {
+                break;
+            }
+            tree[*index] = DscNode::Parent {
+                left: next_node_index,
+                right: next_node_index + 1,
+            };
+            next_level.push(next_node_index);
+            next_level.push(next_node_index + 1);
+            next_node_index += 2;
+        }
+        level = next_level;
+        depth += 1;
+    }
+    tree
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DscNode {
+    Parent { left: usize, right: usize },
+    Leaf(u16),
+}
+
+#[derive(Debug)]
+struct DscBitReader<'a> {
+    buf: &'a [u8],
+    index: usize,
+    bits: u32,
+    bit_count: u32,
+}
+
+impl<'a> DscBitReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            index: 0,
+            bits: 0,
+            bit_count: 0,
+        }
+    }
+    fn read(&mut self, count: u32) -> anyhow::Result<u32> {
+        let mut ret = 0;
+        for _ in 0..count {
+            if self.bit_count == 0 {
+                self.bits = *self
+                    .buf
+                    .get(self.index)
+                    .context("Unexpected end of DSC data")?
+                    as u32;
+                self.index += 1;
+                self.bit_count = 8;
+            }
+            self.bit_count -= 1;
+            ret = (ret << 1) | ((self.bits >> self.bit_count) & 1);
+        }
+        Ok(ret)
+    }
+}
+
 #[derive(Debug)]
 struct Buriko {
     header: BurikoHeader,
@@ -164,7 +335,7 @@ impl<'a> ctx::TryFromCtx<'a, BurikoHeader> for Buriko {
         let off = &mut 0;
         let mut file_entries = Vec::with_capacity(header.entry_count as usize);
         for _ in 0..header.entry_count {
-            file_entries.push(buf.gread(off)?);
+            file_entries.push(buf.gread_with(off, &header)?);
         }
         Ok((
             Self {
@@ -179,9 +350,12 @@ impl<'a> ctx::TryFromCtx<'a, BurikoHeader> for Buriko {
 #[derive(Debug, Copy, Clone)]
 struct BurikoHeader {
     magic: [u8; 10],
+    /// 1 for `PackFile    ` archives, 20 for `BURIKO ARC20`
     version: u16,
     entry_count: u32,
     file_contents_offset: u64,
+    entry_size: usize,
+    entry_name_size: usize,
 }
 
 impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for BurikoHeader {
@@ -195,22 +369,33 @@ impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for BurikoHeader {
         let magic: [u8; 10] =
             buf.get(0..10).context("Out of bounds access")?.try_into()?;
         *off += magic.len();
-        let version = String::from_utf8(
-            buf.get(*off..*off + 2)
-                .context("Out of bounds access")?
-                .to_vec(),
-        )?
-        .parse()?;
+        let (version, entry_size, entry_name_size) =
+            if buf.starts_with(PACKFILE_MAGIC) {
+                (1, PACKFILE_ENTRY_SIZE, PACKFILE_ENTRY_NAME_SIZE)
+            } else {
+                (
+                    String::from_utf8(
+                        buf.get(*off..*off + 2)
+                            .context("Out of bounds access")?
+                            .to_vec(),
+                    )?
+                    .parse()?,
+                    BURIKO_ENTRY_SIZE,
+                    BURIKO_ENTRY_NAME_SIZE,
+                )
+            };
         *off += 2;
         let entry_count = buf.gread_with::<u32>(off, LE)?;
         let file_contents_offset =
-            0x10 + entry_count as u64 * BURIKO_ENTRY_SIZE as u64;
+            0x10 + entry_count as u64 * entry_size as u64;
         Ok((
             Self {
                 magic,
                 version,
                 entry_count,
                 file_contents_offset,
+                entry_size,
+                entry_name_size,
             },
             *off,
         ))
@@ -222,21 +407,21 @@ struct BurikoFileEntry {
     full_path: PathBuf,
     file_offset: u32,
     file_size: u32,
-    unknown: [u8; 18],
+    unknown: Vec<u8>,
 }
 
-impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for BurikoFileEntry {
+impl<'a> ctx::TryFromCtx<'a, &BurikoHeader> for BurikoFileEntry {
     type Error = anyhow::Error;
 
     fn try_from_ctx(
         buf: &'a [u8],
-        _: scroll::Endian,
+        header: &BurikoHeader,
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
         let full_path = PathBuf::from(
             SHIFT_JIS
                 .decode(
-                    buf.get(*off..*off + BURIKO_ENTRY_NAME_SIZE)
+                    buf.get(*off..*off + header.entry_name_size)
                         .context("Out of bounds access")?
                         .split(|b| *b == 0)
                         .next()
@@ -245,13 +430,13 @@ impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for BurikoFileEntry {
                 .0
                 .to_string(),
         );
-        *off += BURIKO_ENTRY_NAME_SIZE;
+        *off += header.entry_name_size;
         let file_offset = buf.gread_with::<u32>(off, LE)?;
         let file_size = buf.gread_with::<u32>(off, LE)?;
-        let unknown: [u8; 18] = buf
-            .get(*off..*off + 18)
+        let unknown = buf
+            .get(*off..header.entry_size)
             .context("Out of bounds access")?
-            .try_into()?;
+            .to_vec();
         *off += unknown.len();
         Ok((
             Self {
@@ -260,7 +445,7 @@ impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for BurikoFileEntry {
                 file_size,
                 unknown,
             },
-            BURIKO_ENTRY_SIZE,
+            header.entry_size,
         ))
     }
 }
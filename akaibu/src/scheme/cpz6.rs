@@ -0,0 +1,511 @@
+//! CPZ5 and CPZ6 archives. Both versions share header fields, directory
+//! and file entries, so they are handled by single scheme. CPZ5 differs only
+//! in shorter header without file decrypt key, decrypt table shuffled with
+//! linear congruential generator and file entries decrypt buffer.
+
+use super::{
+    cpz7::{
+        decrypt_archive_data, decrypt_file, decrypt_with_decrypt_table,
+        decrypt_with_password, get_decrypt_buf, init_decrypt_table,
+        internal_decrypt_file_data, PASSWORD,
+    },
+    Scheme,
+};
+use crate::{archive, error::AkaibuError, options::Options, util::md5};
+use anyhow::Context;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use encoding_rs::SHIFT_JIS;
+use positioned_io::{RandomAccessFile, ReadAt};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scroll::{ctx, Pread, LE};
+use std::{convert::TryInto, fs::File, io::Write, path::PathBuf};
+
+/// Used to decrypt header fields
+const HEADER_KEYS: [u32; 10] = [
+    0xFE3A53D9, 0x37F298E7, 0x7A6F3A2C, 0x43DE7C19, 0xCC65F415, 0xD016A93C,
+    0x97A3BA9A, 0xAE7D39BF, 0xFB73A955, 0x37ACF831,
+];
+const CPZ5_HEADER_SIZE: usize = 0x40;
+const CPZ6_HEADER_SIZE: usize = 0x48;
+const DIRECTORY_ENTRY_HEADER_SIZE: usize = 0x10;
+const FILE_ENTRY_HEADER_SIZE: usize = 0x18;
+
+#[derive(Debug, Clone)]
+pub enum Cpz6Scheme {
+    Universal,
+}
+
+impl Scheme for Cpz6Scheme {
+    fn extract(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<(
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    )> {
+        let mut buf = vec![0; CPZ6_HEADER_SIZE];
+        let file = RandomAccessFile::open(file_path)?;
+        file.read_exact_at(0, &mut buf)?;
+        let header = buf.pread::<Cpz6Header>(0)?;
+        log::debug!("Header: {:#X?}", header);
+
+        let mut buf = vec![
+            0;
+            header.directory_entries_size as usize
+                + header.file_entries_size as usize
+        ];
+        file.read_exact_at(header.header_size as u64, &mut buf)?;
+        let archive = buf.pread_with::<Cpz6>(0, header)?;
+        log::debug!("Archive: {:#?}", archive);
+
+        let root_dir = Cpz6Archive::new_root_dir(&archive.file_entries);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((Box::new(Cpz6Archive { file, archive }), navigable_dir))
+    }
+    fn get_name(&self) -> String {
+        format!(
+            "[CPZ5/CPZ6] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+    fn get_schemes() -> Vec<Box<dyn Scheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+#[derive(Debug)]
+struct Cpz6Archive {
+    file: RandomAccessFile,
+    archive: Cpz6,
+}
+
+impl archive::Archive for Cpz6Archive {
+    fn extract(&self, entry: &archive::FileEntry) -> anyhow::Result<Bytes> {
+        self.archive
+            .file_entries
+            .iter()
+            .find(|e| e.full_path == entry.full_path)
+            .map(|e| self.extract(e))
+            .context("File not found")?
+    }
+
+    fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
+        self.archive.file_entries.par_iter().try_for_each(|entry| {
+            let buf = self.extract(entry)?;
+            let mut output_file_name = PathBuf::from(output_path);
+            output_file_name.push(&entry.full_path);
+            std::fs::create_dir_all(
+                &output_file_name
+                    .parent()
+                    .context("Could not get parent directory")?,
+            )?;
+            log::debug!(
+                "Extracting resource: {:?} {:X?}",
+                output_file_name,
+                entry
+            );
+            File::create(output_file_name)?.write_all(&buf)?;
+            Ok(())
+        })
+    }
+}
+
+impl Cpz6Archive {
+    fn new_root_dir(entries: &[Cpz6FileEntry]) -> archive::Directory {
+        archive::Directory::new(
+            entries
+                .iter()
+                .map(|entry| {
+                    let file_offset = entry.file_offset;
+                    let file_size = entry.file_size as u64;
+                    archive::FileEntry {
+                        file_name: String::from(
+                            entry
+                                .full_path
+                                .file_name()
+                                .expect("No file name")
+                                .to_str()
+                                .expect("Not valid UTF-8"),
+                        ),
+                        full_path: entry.full_path.clone(),
+                        file_offset,
+                        file_size,
+                    }
+                })
+                .collect(),
+        )
+    }
+    fn extract(&self, entry: &Cpz6FileEntry) -> anyhow::Result<Bytes> {
+        let header = &self.archive.header;
+        let mut contents = vec![0; entry.file_size as usize];
+        let raw_file_data_off = header.header_size as u64
+            + header.directory_entries_size as u64
+            + header.file_entries_size as u64;
+        self.file.read_exact_at(
+            raw_file_data_off + entry.file_offset,
+            &mut contents,
+        )?;
+        if !header.is_encrypted {
+            return Ok(Bytes::from(contents));
+        }
+        let mut file_key = entry.file_decrypt_key;
+        file_key ^= header.archive_data_key;
+        file_key = file_key.wrapping_add(header.directory_count);
+        file_key = file_key.wrapping_sub(0x5C39E87B);
+        if header.version == 6 {
+            file_key ^= header
+                .file_decrypt_key
+                .rotate_right(5)
+                .wrapping_mul(0x7DA8F173)
+                .wrapping_add(0x13712765);
+        }
+        decrypt_file(
+            &contents,
+            entry.file_size as usize,
+            &self.archive.md5_cpz,
+            file_key,
+            &self.archive.files_decrypt_table,
+            PASSWORD,
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Cpz6 {
+    header: Cpz6Header,
+    file_entries: Vec<Cpz6FileEntry>,
+    files_decrypt_table: Bytes,
+    md5_cpz: [u8; 16],
+}
+
+impl<'a> ctx::TryFromCtx<'a, Cpz6Header> for Cpz6 {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        header: Cpz6Header,
+    ) -> Result<(Self, usize), Self::Error> {
+        let directory_entries_size = header.directory_entries_size as usize;
+        let mut raw_data = decrypt_with_password(
+            buf,
+            buf.len(),
+            PASSWORD,
+            header.archive_data_key ^ 0x3795B39A,
+        )?;
+        let md5_cpz = md5_cpz6(&header.cpz_md5)?;
+        let directory_decrypt_table = header.init_decrypt_table(
+            header.archive_data_key,
+            md5_cpz.pread_with::<u32>(4, LE)?,
+        )?;
+        decrypt_with_decrypt_table(
+            &directory_decrypt_table,
+            &mut raw_data,
+            directory_entries_size,
+            0x3A,
+        )?;
+        let decrypt_buf = get_decrypt_buf(&md5_cpz, header.archive_data_key);
+        let raw_directories = decrypt_archive_data(
+            &decrypt_buf,
+            raw_data
+                .get(..directory_entries_size)
+                .context("Out of bounds access")?,
+            0,
+        )?;
+        let off = &mut 0;
+        let mut directories = Vec::with_capacity(
+            (header.directory_count as usize)
+                .min(directory_entries_size / DIRECTORY_ENTRY_HEADER_SIZE),
+        );
+        for _ in 0..header.directory_count {
+            directories.push(raw_directories.gread::<Cpz6DirectoryEntry>(off)?);
+        }
+
+        let file_entries_decrypt_table = header.init_decrypt_table(
+            header.archive_data_key,
+            md5_cpz.pread_with::<u32>(8, LE)?,
+        )?;
+        let raw_file_entries = raw_data
+            .get_mut(directory_entries_size..)
+            .context("Out of bounds access")?;
+        let mut file_entries = Vec::new();
+        for (i, directory) in directories.iter().enumerate() {
+            let start = directory.file_entries_offset as usize;
+            let end = match directories.get(i + 1) {
+                Some(next) => next.file_entries_offset as usize,
+                None => header.file_entries_size as usize,
+            };
+            let raw_entries = raw_file_entries
+                .get_mut(start..end)
+                .context("Invalid directory file entries offset")?;
+            decrypt_with_decrypt_table(
+                &file_entries_decrypt_table,
+                raw_entries,
+                end - start,
+                0x7E,
+            )?;
+            let decrypt_buf =
+                header.get_file_entries_decrypt_buf(&md5_cpz, directory.key);
+            let raw_entries =
+                internal_decrypt_file_data(&decrypt_buf, raw_entries, 0)?;
+            let off = &mut 0;
+            for _ in 0..directory.file_count {
+                file_entries.push(
+                    raw_entries.gread_with::<Cpz6FileEntry>(off, directory)?,
+                );
+            }
+        }
+        let files_decrypt_table = header.init_decrypt_table(
+            md5_cpz.pread_with(12, LE)?,
+            header.archive_data_key,
+        )?;
+        Ok((
+            Self {
+                header,
+                file_entries,
+                files_decrypt_table,
+                md5_cpz,
+            },
+            buf.len(),
+        ))
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Cpz6Header {
+    version: u8,
+    header_size: usize,
+    directory_count: u32,
+    directory_entries_size: u32,
+    file_entries_size: u32,
+    cpz_md5: [u8; 16],
+    archive_data_key: u32,
+    is_encrypted: bool,
+    file_decrypt_key: u32,
+}
+
+impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for Cpz6Header {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        _: scroll::Endian,
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 3;
+        let version = buf.gread::<u8>(off)?.wrapping_sub(b'0');
+        let header_size = match version {
+            5 => CPZ5_HEADER_SIZE,
+            6 => CPZ6_HEADER_SIZE,
+            _ => {
+                return Err(AkaibuError::Unimplemented(format!(
+                    "CPZ version: {} is not supported",
+                    version
+                ))
+                .into())
+            }
+        };
+        let directory_count = buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[0];
+        let directory_entries_size =
+            buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[1];
+        let file_entries_size =
+            buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[2];
+        // Skip md5 of unencrypted data
+        *off += 16;
+        let mut cpz_md5: [u8; 16] = buf
+            .get(*off..*off + 16)
+            .context("Out of bounds access")?
+            .try_into()?;
+        cpz_md5.chunks_mut(4).enumerate().for_each(|(i, c)| {
+            c[0] ^= HEADER_KEYS[i + 3] as u8;
+            c[1] ^= (HEADER_KEYS[i + 3] >> 8) as u8;
+            c[2] ^= (HEADER_KEYS[i + 3] >> 16) as u8;
+            c[3] ^= (HEADER_KEYS[i + 3] >> 24) as u8;
+        });
+        *off += 16;
+        let archive_data_key = buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[7];
+        let is_encrypted =
+            buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[8] != 0;
+        let file_decrypt_key = if version == 6 {
+            buf.gread_with::<u32>(off, LE)? ^ HEADER_KEYS[9]
+        } else {
+            0
+        };
+        Ok((
+            Self {
+                version,
+                header_size,
+                directory_count,
+                directory_entries_size,
+                file_entries_size,
+                cpz_md5,
+                archive_data_key,
+                is_encrypted,
+                file_decrypt_key,
+            },
+            header_size,
+        ))
+    }
+}
+
+impl Cpz6Header {
+    fn init_decrypt_table(
+        &self,
+        key1: u32,
+        key2: u32,
+    ) -> anyhow::Result<Bytes> {
+        if self.version == 5 {
+            Ok(init_cpz5_decrypt_table(key1, key2))
+        } else {
+            init_decrypt_table(key1, key2)
+        }
+    }
+    fn get_file_entries_decrypt_buf(&self, md5_cpz: &[u8], key: u32) -> Bytes {
+        let mut src = Bytes::copy_from_slice(&md5_cpz);
+        let mut dest = BytesMut::with_capacity(16);
+        if self.version == 5 {
+            (0..4).for_each(|_| dest.put_u32_le(key ^ src.get_u32_le()));
+        } else {
+            dest.put_u32_le(key ^ src.get_u32_le());
+            dest.put_u32_le(key.wrapping_add(0x112233) ^ src.get_u32_le());
+            dest.put_u32_le(key ^ src.get_u32_le());
+            dest.put_u32_le(key.wrapping_add(0x34258765) ^ src.get_u32_le());
+        }
+        dest.freeze()
+    }
+}
+
+#[derive(Debug)]
+struct Cpz6DirectoryEntry {
+    file_count: u32,
+    file_entries_offset: u32,
+    key: u32,
+    name: String,
+}
+
+impl<'a> ctx::TryFromCtx<'a, ()> for Cpz6DirectoryEntry {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        _: (),
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let entry_size = buf.gread_with::<u32>(off, LE)?;
+        let file_count = buf.gread_with::<u32>(off, LE)?;
+        let file_entries_offset = buf.gread_with::<u32>(off, LE)?;
+        let key = buf.gread_with::<u32>(off, LE)?;
+        if (entry_size as usize) <= DIRECTORY_ENTRY_HEADER_SIZE
+            || file_count as usize > buf.len()
+        {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid directory entry size: {} file count: {}",
+                entry_size, file_count
+            ))
+            .into());
+        }
+        let name = SHIFT_JIS
+            .decode(
+                buf.get(*off..entry_size as usize)
+                    .context("Out of bounds access")?
+                    .split(|b| *b == 0)
+                    .next()
+                    .context("Could not split")?,
+            )
+            .0
+            .to_string();
+        Ok((
+            Self {
+                file_count,
+                file_entries_offset,
+                key,
+                name,
+            },
+            entry_size as usize,
+        ))
+    }
+}
+
+#[derive(Debug)]
+struct Cpz6FileEntry {
+    file_offset: u64,
+    file_size: u32,
+    file_decrypt_key: u32,
+    full_path: PathBuf,
+}
+
+impl<'a> ctx::TryFromCtx<'a, &Cpz6DirectoryEntry> for Cpz6FileEntry {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        directory: &Cpz6DirectoryEntry,
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let entry_size = buf.gread_with::<u32>(off, LE)?;
+        if (entry_size as usize) <= FILE_ENTRY_HEADER_SIZE {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid file entry size: {}",
+                entry_size
+            ))
+            .into());
+        }
+        let file_offset = buf.gread_with::<u64>(off, LE)?;
+        let file_size = buf.gread_with::<u32>(off, LE)?;
+        // Skip checksum of file data
+        *off += 4;
+        let file_decrypt_key =
+            buf.gread_with::<u32>(off, LE)?.wrapping_add(directory.key);
+        let name = SHIFT_JIS
+            .decode(
+                buf.get(*off..entry_size as usize)
+                    .context("Out of bounds access")?
+                    .split(|b| *b == 0)
+                    .next()
+                    .context("Could not split")?,
+            )
+            .0
+            .replace("\\", "/");
+        let full_path = if directory.name == "root" {
+            PathBuf::from(name)
+        } else {
+            PathBuf::from(format!("{}/{}", directory.name, name))
+        };
+        Ok((
+            Self {
+                file_offset,
+                file_size,
+                file_decrypt_key,
+                full_path,
+            },
+            entry_size as usize,
+        ))
+    }
+}
+
+/// CPZ5 uses linear congruential generator to shuffle decrypt table
+fn init_cpz5_decrypt_table(mut key: u32, summand: u32) -> Bytes {
+    let mut table = (0..=255).collect::<Vec<u8>>();
+    for i in 0..table.len() {
+        key = key.wrapping_mul(0x1A743125).wrapping_add(summand);
+        table.swap(i, (key >> 16) as usize & 0xFF);
+    }
+    Bytes::from(table)
+}
+
+fn md5_cpz6(buf: &[u8]) -> anyhow::Result<[u8; 16]> {
+    let mut result = Bytes::copy_from_slice(&md5::compute(
+        &buf,
+        [0xC74A2B01, 0xE7C8AB8F, 0xD8BEDC4E, 0x7302A4C5],
+    ));
+    let mut digest = BytesMut::with_capacity(16);
+    let a = result.get_u32_le();
+    let b = result.get_u32_le();
+    let c = result.get_u32_le();
+    let d = result.get_u32_le();
+    digest.put_u32_le(d ^ 0x49875325);
+    digest.put_u32_le(b.wrapping_add(0x54F46D7D));
+    digest.put_u32_le(c ^ 0xAD7948B7);
+    digest.put_u32_le(a.wrapping_add(0x1D0638AD));
+    Ok(digest.as_ref().try_into()?)
+}
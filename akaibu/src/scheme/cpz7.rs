@@ -1,8 +1,9 @@
 use super::Scheme;
-use crate::{archive, util::md5};
+use crate::{archive, error::AkaibuError, options::Options, util::md5};
 use anyhow::Context;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use encoding_rs::SHIFT_JIS;
+use itertools::Itertools;
 use positioned_io::{RandomAccessFile, ReadAt};
 use scroll::{ctx, Pread, LE};
 use std::{
@@ -16,7 +17,7 @@ const HEADER_KEYS: [u32; 12] = [
 ];
 
 /// Used to decrypt files
-const PASSWORD: &[u8] = &[
+pub(super) const PASSWORD: &[u8] = &[
     137, 240, 144, 205, 130, 183, 130, 233, 136, 171, 130, 162, 142, 113, 130,
     205, 131, 138, 131, 82, 130, 170, 130, 168, 142, 100, 146, 117, 130, 171,
     130, 181, 130, 191, 130, 225, 130, 162, 130, 220, 130, 183, 129, 66, 142,
@@ -26,6 +27,14 @@ const PASSWORD: &[u8] = &[
     130, 181, 130, 189, 129, 244,
 ];
 
+/// Offset of file contents relative to end of archive, file and encryption data
+const RAW_FILE_DATA_OFFSET: u32 = 0x48;
+/// Amount of file entries checked for known magic when validating game keys
+const KEYS_VALIDATION_ENTRIES: usize = 8;
+/// Magic values of file types stored in CPZ7 archives
+const KNOWN_MAGICS: [&[u8]; 5] =
+    [b"PB3B", b"PS2A", b"OggS", b"RIFF", b"\x89PNG"];
+
 #[derive(Debug, Clone)]
 pub enum Cpz7Scheme {
     AoiTori,
@@ -37,6 +46,7 @@ impl Scheme for Cpz7Scheme {
     fn extract(
         &self,
         file_path: &PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn archive::Archive + Sync>,
         archive::NavigableDirectory,
@@ -53,29 +63,71 @@ impl Scheme for Cpz7Scheme {
                 + cpz_header.encryption_data_size as usize
         ];
         file.read_exact_at(72, &mut buf)?;
-        let all_game_keys = self.get_game_keys()?;
-        let game_keys = *all_game_keys
-            .get(
-                file_path
-                    .file_name()
-                    .context("Could not get file name")?
-                    .to_str()
-                    .context("Could not parse OsStr to str")?,
+        let file_name = file_path
+            .file_name()
+            .context("Could not get file name")?
+            .to_str()
+            .context("Could not parse OsStr to str")?;
+        let file_contents_size = std::fs::metadata(file_path)?
+            .len()
+            .checked_sub(
+                cpz_header.archive_data_size as u64
+                    + cpz_header.file_data_size as u64
+                    + cpz_header.encryption_data_size as u64
+                    + RAW_FILE_DATA_OFFSET as u64,
             )
-            .unwrap_or(&[0, 0, 0, 0]);
-        let archive = buf.pread_with::<Cpz7>(0, (cpz_header, &game_keys))?;
-        log::debug!("Archive: {:#?}", archive.file_data.values());
+            .context("Archive is truncated")?;
 
-        let root_dir = Cpz7Archive::new_root_dir(&archive);
-        let navigable_dir = archive::NavigableDirectory::new(root_dir);
-        Ok((
-            Box::new(Cpz7Archive {
-                file,
+        // Game keys assigned to archive file name are used when no other key set
+        // could be verified
+        let mut fallback = None;
+        let mut tried = Vec::new();
+        for (keys_name, game_keys) in self.get_candidate_keys(file_name)? {
+            let archive = match buf
+                .pread_with::<Cpz7>(0, (cpz_header, &game_keys))
+            {
+                Ok(archive) if archive.is_valid(file_contents_size) => archive,
+                _ => {
+                    tried.push(format!("{} (invalid file table)", keys_name));
+                    continue;
+                }
+            };
+            let archive = Cpz7Archive {
+                file: RandomAccessFile::open(file_path)?,
                 game_keys,
                 archive,
-            }),
-            navigable_dir,
-        ))
+            };
+            if archive.has_known_magic()? {
+                if keys_name != file_name {
+                    log::warn!(
+                        "Using game keys of {} for archive {}",
+                        keys_name,
+                        file_name
+                    );
+                }
+                return Ok(archive.into_navigable());
+            }
+            tried.push(format!("{} (unknown file contents)", keys_name));
+            if keys_name == file_name {
+                fallback = Some(archive);
+            }
+        }
+        match fallback {
+            Some(archive) => {
+                log::warn!(
+                    "Could not verify game keys for archive {}",
+                    file_name
+                );
+                Ok(archive.into_navigable())
+            }
+            None => Err(AkaibuError::Custom(format!(
+                "Could not find valid game keys for archive {} in {}. Tried: {}",
+                file_name,
+                self.get_name(),
+                tried.join(", ")
+            ))
+            .into()),
+        }
     }
     fn get_name(&self) -> String {
         format!(
@@ -100,6 +152,26 @@ impl Scheme for Cpz7Scheme {
 }
 
 impl Cpz7Scheme {
+    /// Get all distinct key sets of selected game.
+    /// Key set assigned to archive file name comes first.
+    fn get_candidate_keys(
+        &self,
+        file_name: &str,
+    ) -> anyhow::Result<Vec<(String, [u32; 4])>> {
+        let all_game_keys = self.get_game_keys()?;
+        let mut candidates: Vec<(String, [u32; 4])> = Vec::new();
+        if let Some(game_keys) = all_game_keys.get(file_name) {
+            candidates.push((file_name.to_string(), *game_keys));
+        }
+        for (name, game_keys) in
+            all_game_keys.into_iter().sorted_by(|a, b| a.0.cmp(&b.0))
+        {
+            if !candidates.iter().any(|(_, keys)| *keys == game_keys) {
+                candidates.push((name, game_keys));
+            }
+        }
+        Ok(candidates)
+    }
     fn get_game_keys(&self) -> anyhow::Result<HashMap<String, [u32; 4]>> {
         Ok(match self {
             Cpz7Scheme::AoiTori => serde_json::from_slice(
@@ -166,6 +238,17 @@ impl archive::Archive for Cpz7Archive {
 }
 
 impl Cpz7Archive {
+    fn into_navigable(
+        self,
+    ) -> (
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    ) {
+        log::debug!("Archive: {:#?}", self.archive.file_data.values());
+        let root_dir = Cpz7Archive::new_root_dir(&self.archive);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        (Box::new(self), navigable_dir)
+    }
     fn new_root_dir(archive: &Cpz7) -> archive::Directory {
         archive::Directory::new(
             archive
@@ -192,12 +275,37 @@ impl Cpz7Archive {
                 .collect(),
         )
     }
+    /// Check if any of first few file entries decrypts to known file type
+    fn has_known_magic(&self) -> anyhow::Result<bool> {
+        for entry in self
+            .archive
+            .file_data
+            .values()
+            .flatten()
+            .take(KEYS_VALIDATION_ENTRIES)
+        {
+            let buf = self.extract_prefix(entry, 16)?;
+            if KNOWN_MAGICS.iter().any(|magic| buf.starts_with(magic)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
     fn extract(&self, entry: &FileEntry) -> anyhow::Result<Bytes> {
-        let mut contents = vec![0; entry.file_size as usize];
+        self.extract_prefix(entry, entry.file_size as usize)
+    }
+    /// Decrypt only first `size` bytes of file
+    fn extract_prefix(
+        &self,
+        entry: &FileEntry,
+        size: usize,
+    ) -> anyhow::Result<Bytes> {
+        let size = size.min(entry.file_size as usize);
+        let mut contents = vec![0; size];
         let raw_file_data_off = self.archive.header.archive_data_size
             + self.archive.header.file_data_size
             + self.archive.header.encryption_data_size
-            + 0x48;
+            + RAW_FILE_DATA_OFFSET;
         self.file.read_exact_at(
             raw_file_data_off as u64 + entry.file_offset as u64,
             &mut contents,
@@ -211,7 +319,7 @@ impl Cpz7Archive {
         );
         decrypt_file(
             &contents,
-            entry.file_size as usize,
+            size,
             &self.archive.md5_cpz7,
             file_key,
             &self.archive.files_decrypt_table,
@@ -229,6 +337,23 @@ struct Cpz7 {
     encryption_data: EncryptionData,
 }
 
+impl Cpz7 {
+    /// File table decrypted with wrong game keys contains garbage
+    fn is_valid(&self, file_contents_size: u64) -> bool {
+        self.file_data.iter().all(|(archive, files)| {
+            !archive.name.contains('\u{FFFD}')
+                && files.iter().all(|file| {
+                    file.file_offset as u64 + file.file_size as u64
+                        <= file_contents_size
+                        && !file
+                            .full_path
+                            .to_string_lossy()
+                            .contains('\u{FFFD}')
+                })
+        })
+    }
+}
+
 impl<'a> ctx::TryFromCtx<'a, (Cpz7Header, &[u32; 4])> for Cpz7 {
     type Error = anyhow::Error;
     fn try_from_ctx(
@@ -305,6 +430,13 @@ impl<'a> ctx::TryFromCtx<'a, (Cpz7Header, &[u32; 4])> for Cpz7 {
         let mut file_data = HashMap::new();
         let off = &mut 0;
         for archive in archive_data {
+            if archive.file_count as usize > raw_file_data.len() / 0x1C {
+                return Err(AkaibuError::Custom(format!(
+                    "Invalid file count: {}",
+                    archive.file_count
+                ))
+                .into());
+            }
             let mut file_entries =
                 Vec::with_capacity(archive.file_count as usize);
             for _ in 0..archive.file_count {
@@ -416,8 +548,13 @@ impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for ArchiveDataEntry {
         let file_decrypt_key = buf.gread_with(off, LE)?;
         let name = SHIFT_JIS
             .decode(
-                buf.get(*off..*off + entry_size as usize - 0x10)
-                    .context("Out of bounds access")?,
+                buf.get(
+                    *off..*off
+                        + (entry_size as usize)
+                            .checked_sub(0x10)
+                            .context("Invalid entry size")?,
+                )
+                .context("Out of bounds access")?,
             )
             .0
             .to_string()
@@ -469,8 +606,13 @@ impl<'a> ctx::TryFromCtx<'a, &ArchiveDataEntry> for FileEntry {
             archive.name,
             SHIFT_JIS
                 .decode(
-                    buf.get(*off..*off + entry_size as usize - 0x1C)
-                        .context("Out of bounds access")?
+                    buf.get(
+                        *off..*off
+                            + (entry_size as usize)
+                                .checked_sub(0x1C)
+                                .context("Invalid entry size")?
+                    )
+                    .context("Out of bounds access")?
                 )
                 .0
                 .to_string()
@@ -678,7 +820,7 @@ impl EncryptionData {
     }
 }
 
-fn decrypt_with_password(
+pub(super) fn decrypt_with_password(
     buf: &[u8],
     size: usize,
     password: &[u8],
@@ -722,7 +864,10 @@ fn decrypt_with_password(
     Ok(result)
 }
 
-fn init_decrypt_table(key1: u32, key2: u32) -> anyhow::Result<Bytes> {
+pub(super) fn init_decrypt_table(
+    key1: u32,
+    key2: u32,
+) -> anyhow::Result<Bytes> {
     let mut table = BytesMut::with_capacity(0x100);
     for i in 0..=255 {
         table.put_u8(i);
@@ -756,7 +901,7 @@ fn init_decrypt_table(key1: u32, key2: u32) -> anyhow::Result<Bytes> {
     Ok(table.freeze())
 }
 
-fn decrypt_with_decrypt_table(
+pub(super) fn decrypt_with_decrypt_table(
     table: &[u8],
     data: &mut [u8],
     size: usize,
@@ -770,7 +915,7 @@ fn decrypt_with_decrypt_table(
     })
 }
 
-fn get_decrypt_buf(md5_cpz7: &[u8], key: u32) -> Bytes {
+pub(super) fn get_decrypt_buf(md5_cpz7: &[u8], key: u32) -> Bytes {
     let mut src = Bytes::copy_from_slice(&md5_cpz7);
     let mut dest = BytesMut::with_capacity(16);
     dest.put_u32_le(key.wrapping_add(0x76A3BF29) ^ src.get_u32_le());
@@ -780,7 +925,7 @@ fn get_decrypt_buf(md5_cpz7: &[u8], key: u32) -> Bytes {
     dest.freeze()
 }
 
-fn decrypt_archive_data(
+pub(super) fn decrypt_archive_data(
     decrypt_buf: &[u8],
     data: &[u8],
     key1: u32,
@@ -831,7 +976,7 @@ fn decrypt_file_data(
                 .context("Out of bounds access")?
                 .offset;
         }
-        size -= offset;
+        size = size.checked_sub(offset).context("Invalid archive offset")?;
         decrypt_with_decrypt_table(
             &table,
             raw_file_data
@@ -863,7 +1008,7 @@ fn get_decrypt_buf2(md5_cpz7: &[u8], key: u32) -> Bytes {
     dest.freeze()
 }
 
-fn internal_decrypt_file_data(
+pub(super) fn internal_decrypt_file_data(
     decrypt_buf: &[u8],
     data: &[u8],
     key2: u32,
@@ -919,7 +1064,7 @@ fn get_file_key(
     file_key
 }
 
-fn decrypt_file(
+pub(super) fn decrypt_file(
     file_contents: &[u8],
     file_size: usize,
     md5_cpz7: &[u8],
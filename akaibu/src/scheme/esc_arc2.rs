@@ -1,4 +1,4 @@
-use crate::archive;
+use crate::{archive, error::AkaibuError, options::Options};
 
 use super::Scheme;
 use anyhow::Context;
@@ -6,11 +6,18 @@ use bytes::{Bytes, BytesMut};
 use encoding_rs::SHIFT_JIS;
 use positioned_io::{RandomAccessFile, ReadAt};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use scroll::{ctx, Pread, LE};
+use scroll::{ctx, Pread, BE, LE};
 use std::{fs::File, io::Write, path::PathBuf};
 
 const KEY: u32 = 0x65AC9365;
 const FILE_ENTRY_SIZE: usize = 12;
+const ARC1_FILE_ENTRY_SIZE: usize = 0x88;
+const ARC1_FILE_NAME_SIZE: usize = 0x80;
+const ARC1_HEADER_SIZE: usize = 16;
+const ACP_MAGIC: &[u8] = b"acp\0";
+const ACP_HEADER_SIZE: usize = 8;
+const LZW_DICTIONARY_SIZE: usize = 0x8900;
+const LZW_MAX_TOKEN_WIDTH: u32 = 24;
 
 #[derive(Debug, Clone)]
 pub enum EscArc2Scheme {
@@ -21,6 +28,7 @@ impl Scheme for EscArc2Scheme {
     fn extract(
         &self,
         file_path: &std::path::PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         crate::archive::NavigableDirectory,
@@ -28,6 +36,9 @@ impl Scheme for EscArc2Scheme {
         let mut buf = vec![0; 20];
         let file = RandomAccessFile::open(file_path)?;
         file.read_exact_at(0, &mut buf)?;
+        if buf.starts_with(b"ESC-ARC1") {
+            return Self::extract_arc1(file, &buf);
+        }
 
         let header = buf.pread::<EscArc2Header>(0)?;
         log::debug!("Header: {:#?}", header);
@@ -73,6 +84,41 @@ impl Scheme for EscArc2Scheme {
     }
 }
 
+impl EscArc2Scheme {
+    /// ESC-ARC1 has no name table, file names are stored in index
+    fn extract_arc1(
+        file: RandomAccessFile,
+        buf: &[u8],
+    ) -> anyhow::Result<(
+        Box<dyn crate::archive::Archive + Sync>,
+        crate::archive::NavigableDirectory,
+    )> {
+        let mut key = buf.pread_with::<u32>(8, LE)?;
+        let file_count = buf.pread_with::<u32>(12, LE)? ^ next_key(&mut key);
+        let mut file_entries =
+            vec![0; file_count as usize * ARC1_FILE_ENTRY_SIZE];
+        file.read_exact_at(ARC1_HEADER_SIZE as u64, &mut file_entries)?;
+        decrypt(&mut file_entries, &mut key);
+        let file_entries = file_entries
+            .chunks_exact(ARC1_FILE_ENTRY_SIZE)
+            .map(EscArc2FileEntry::from_arc1)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let archive = EscArc2 {
+            header: EscArc2Header {
+                file_count,
+                file_entry_key: key,
+                file_name_table_size: 0,
+            },
+            file_entries,
+        };
+        log::debug!("Archive: {:#?}", archive);
+
+        let root_dir = EscArc2Archive::new_root_dir(&archive.file_entries);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((Box::new(EscArc2Archive { file, archive }), navigable_dir))
+    }
+}
+
 #[derive(Debug)]
 struct EscArc2Archive {
     file: RandomAccessFile,
@@ -138,6 +184,9 @@ impl EscArc2Archive {
         buf.resize(entry.file_size as usize, 0);
         self.file
             .read_exact_at(entry.file_offset as u64, &mut buf)?;
+        if buf.starts_with(ACP_MAGIC) {
+            return Ok(Bytes::from(acp_decompress(&buf)?));
+        }
         Ok(buf.freeze())
     }
 }
@@ -254,29 +303,69 @@ impl<'a> ctx::TryFromCtx<'a, &[u8]> for EscArc2FileEntry {
     }
 }
 
+impl EscArc2FileEntry {
+    fn from_arc1(buf: &[u8]) -> anyhow::Result<Self> {
+        let full_path = PathBuf::from(
+            SHIFT_JIS
+                .decode(
+                    &buf[..ARC1_FILE_NAME_SIZE]
+                        .iter()
+                        .take_while(|b| **b != 0)
+                        .copied()
+                        .collect::<Vec<u8>>(),
+                )
+                .0
+                .to_string()
+                .replace("\\", "/"),
+        );
+        let file_name = full_path
+            .file_name()
+            .context("Could not get file name")?
+            .to_str()
+            .context("Not valid UTF-8")?
+            .to_string();
+        let mut off = ARC1_FILE_NAME_SIZE;
+        Ok(Self {
+            file_offset: buf.gread_with::<u32>(&mut off, LE)?,
+            file_size: buf.gread_with::<u32>(&mut off, LE)?,
+            file_name,
+            full_path,
+        })
+    }
+}
+
+fn next_key(key: &mut u32) -> u32 {
+    *key ^= KEY;
+    let mut d = key.wrapping_add(*key);
+    d ^= *key;
+    let mut c = *key;
+    c >>= 1;
+    d = d.wrapping_add(d);
+    c ^= *key;
+    d = d.wrapping_add(d);
+    c >>= 3;
+    d = d.wrapping_add(d);
+    c ^= d;
+    *key ^= c;
+    *key
+}
+
+fn decrypt(buf: &mut [u8], key: &mut u32) {
+    buf.chunks_exact_mut(4).for_each(|chunk| {
+        let k = next_key(key);
+        chunk[0] ^= k as u8;
+        chunk[1] ^= (k >> 8) as u8;
+        chunk[2] ^= (k >> 16) as u8;
+        chunk[3] ^= (k >> 24) as u8;
+    });
+}
+
 fn decrypt_file_entries(
     file_entries: &mut [u8],
     mut file_entry_key: u32,
     file_name_table: &[u8],
 ) -> anyhow::Result<Vec<EscArc2FileEntry>> {
-    file_entries.chunks_exact_mut(4).for_each(|chunk| {
-        file_entry_key ^= KEY;
-        let mut d = file_entry_key.wrapping_add(file_entry_key);
-        d ^= file_entry_key;
-        let mut c = file_entry_key;
-        c >>= 1;
-        d = d.wrapping_add(d);
-        c ^= file_entry_key;
-        d = d.wrapping_add(d);
-        c >>= 3;
-        d = d.wrapping_add(d);
-        c ^= d;
-        file_entry_key ^= c;
-        chunk[0] ^= file_entry_key as u8;
-        chunk[1] ^= (file_entry_key >> 8) as u8;
-        chunk[2] ^= (file_entry_key >> 16) as u8;
-        chunk[3] ^= (file_entry_key >> 24) as u8;
-    });
+    decrypt(file_entries, &mut file_entry_key);
     file_entries
         .chunks_exact(12)
         .try_fold(Vec::new(), |mut v, chunk| {
@@ -284,3 +373,78 @@ fn decrypt_file_entries(
             Ok(v)
         })
 }
+
+/// Decompress `acp` entry. LZW with codes 0x100 end of data, 0x101 token
+/// width increase and 0x102 dictionary reset. Dictionary stores output
+/// positions, entry n spans from its position to start of entry n + 1.
+fn acp_decompress(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let dest_size = buf.pread_with::<u32>(4, BE)? as usize;
+    let src = buf.get(ACP_HEADER_SIZE..).context("Out of bounds access")?;
+    let mut dest = vec![0; dest_size];
+    let mut dictionary = vec![0usize; LZW_DICTIONARY_SIZE];
+    let mut dictionary_pos = 0;
+    let mut token_width = 9;
+    let mut bit_index = 0;
+    let mut dest_off = 0;
+    while dest_off < dest_size {
+        if bit_index + token_width as usize > src.len() * 8 {
+            return Err(AkaibuError::Custom(
+                "Unexpected end of acp data".to_owned(),
+            )
+            .into());
+        }
+        let mut token = 0usize;
+        for _ in 0..token_width {
+            let bit = (src[bit_index / 8] >> (7 - bit_index % 8)) & 1;
+            token = (token << 1) | bit as usize;
+            bit_index += 1;
+        }
+        match token {
+            0x100 => break,
+            0x101 => {
+                token_width += 1;
+                if token_width > LZW_MAX_TOKEN_WIDTH {
+                    return Err(AkaibuError::Custom(
+                        "Invalid acp token width".to_owned(),
+                    )
+                    .into());
+                }
+            }
+            0x102 => {
+                token_width = 9;
+                dictionary_pos = 0;
+            }
+            _ => {
+                if dictionary_pos >= LZW_DICTIONARY_SIZE {
+                    return Err(AkaibuError::Custom(
+                        "acp dictionary overflow".to_owned(),
+                    )
+                    .into());
+                }
+                dictionary[dictionary_pos] = dest_off;
+                dictionary_pos += 1;
+                if token < 0x100 {
+                    dest[dest_off] = token as u8;
+                    dest_off += 1;
+                } else {
+                    let index = token - 0x103;
+                    if index + 1 >= dictionary_pos {
+                        return Err(AkaibuError::Custom(format!(
+                            "Invalid acp token: {:X}",
+                            token
+                        ))
+                        .into());
+                    }
+                    let start = dictionary[index];
+                    let count = (dictionary[index + 1] - start + 1)
+                        .min(dest_size - dest_off);
+                    for i in 0..count {
+                        dest[dest_off + i] = dest[start + i];
+                    }
+                    dest_off += count;
+                }
+            }
+        }
+    }
+    Ok(dest)
+}
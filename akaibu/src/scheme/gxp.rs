@@ -1,5 +1,5 @@
 use super::Scheme;
-use crate::archive;
+use crate::{archive, options::Options};
 use anyhow::Context;
 use bytes::{Bytes, BytesMut};
 use positioned_io::{RandomAccessFile, ReadAt};
@@ -21,6 +21,7 @@ impl Scheme for GxpScheme {
     fn extract(
         &self,
         file_path: &PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn archive::Archive + Sync>,
         archive::NavigableDirectory,
@@ -1,38 +1,179 @@
 use super::Scheme;
-use crate::{archive, error::AkaibuError};
+use crate::{archive, error::AkaibuError, options::Options};
 use anyhow::Context;
 use bytes::{BufMut, Bytes, BytesMut};
 use camellia_rs::{Block, CamelliaCipher};
+use once_cell::sync::OnceCell;
 use positioned_io::{RandomAccessFile, ReadAt};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use scroll::{ctx, Pread, LE};
-use std::{collections::HashMap, fs::File, io::Write, path::PathBuf};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs::File, io::Write, path::PathBuf};
 
 const KEYS_PATH: &str = "malie/keys.json";
 const MAGIC: &[u8] = b"LIBP";
 
+static EMBEDDED_KEYS: OnceCell<BTreeMap<String, MalieKey>> = OnceCell::new();
+static EMBEDDED_CIPHERS: OnceCell<Vec<MalieCipher>> = OnceCell::new();
+
+/// Options shared by all Malie schemes
+#[derive(Debug, Clone, Default)]
+pub struct MalieOptions {
+    /// Keys from user provided key tables
+    keys: BTreeMap<String, MalieKey>,
+}
+
+impl MalieOptions {
+    /// Load additional key tables in the same JSON format as embedded
+    /// `malie/keys.json`
+    pub fn from_key_tables(key_tables: &[PathBuf]) -> anyhow::Result<Self> {
+        let mut keys = BTreeMap::new();
+        for key_table in key_tables {
+            let table = parse_keys(&std::fs::read(key_table)?)
+                .context(format!("Invalid Malie key table: {:?}", key_table))?;
+            log::debug!(
+                "Loaded {} Malie keys from {:?}",
+                table.len(),
+                key_table
+            );
+            keys.extend(table);
+        }
+        Ok(Self { keys })
+    }
+}
+
+fn get_embedded_keys() -> anyhow::Result<&'static BTreeMap<String, MalieKey>> {
+    EMBEDDED_KEYS.get_or_try_init(|| {
+        parse_keys(&crate::Resources::get(KEYS_PATH).context(format!(
+            "Could not find embedded resource: {}",
+            KEYS_PATH
+        ))?)
+    })
+}
+
+fn parse_keys(buf: &[u8]) -> anyhow::Result<BTreeMap<String, MalieKey>> {
+    let keys: BTreeMap<String, MalieKey> = serde_json::from_slice(buf)?;
+    for (name, key) in keys.iter() {
+        if key.key.len() != 16 && key.key.len() != 32 {
+            return Err(AkaibuError::Custom(format!(
+                "Malie key {} has to be 128 or 256 bits long",
+                name
+            ))
+            .into());
+        }
+    }
+    Ok(keys)
+}
+
+/// Ciphers for embedded keys, key schedules are computed only once as
+/// they are tried on every unrecognised archive
+fn get_embedded_ciphers() -> anyhow::Result<&'static Vec<MalieCipher>> {
+    EMBEDDED_CIPHERS.get_or_try_init(|| {
+        get_embedded_keys()?
+            .values()
+            .map(MalieCipher::new)
+            .collect()
+    })
+}
+
+/// Check if header decrypted with cipher starts with LIBP magic
+fn decrypts_header(header: &[u8], cipher: &MalieCipher) -> bool {
+    let mut header = header.to_vec();
+    cipher.decrypt(&mut header, 0).is_ok() && header.starts_with(MAGIC)
+}
+
+/// Camellia output looks random, so header with several zero bytes or made
+/// only of printable ASCII characters belongs to some other format
+fn may_be_encrypted(header: &[u8]) -> bool {
+    header.iter().filter(|b| **b == 0).count() < 4
+        && !header.iter().all(|b| (0x20..0x7F).contains(b))
+}
+
+/// Archive starts with plain LIBP header or header encrypted with one of
+/// known keys
+pub(crate) fn is_malie(buf: &[u8]) -> bool {
+    let header = match buf.get(..16) {
+        Some(header) => header,
+        None => return false,
+    };
+    if header.starts_with(MAGIC) {
+        return true;
+    }
+    if !may_be_encrypted(header) {
+        return false;
+    }
+    match get_embedded_ciphers() {
+        Ok(ciphers) => {
+            ciphers.iter().any(|cipher| decrypts_header(header, cipher))
+        }
+        Err(err) => {
+            log::error!("{}", err);
+            false
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum MalieScheme {
     HaruUso,
     NatsuUso,
+    /// Game from embedded key table
+    KeyTable(String),
+    /// Key from user provided key tables which decrypts archive header
+    UserKeyTable,
+    Unencrypted,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MalieKey {
+    /// Camellia key, 128 or 256 bits
+    key: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct MalieCipher {
+    camellia: CamelliaCipher,
+}
+
+impl MalieCipher {
+    fn new(key: &MalieKey) -> anyhow::Result<Self> {
+        Ok(Self {
+            camellia: CamelliaCipher::new(&key.key).map_err(|_| {
+                AkaibuError::Custom("Invalid Camellia key length".to_owned())
+            })?,
+        })
+    }
+    fn decrypt(&self, buf: &mut [u8], n: u32) -> anyhow::Result<()> {
+        let rotated = rotate_buffer(buf, n)?;
+        let mut block = Block::default();
+        block.bytes.copy_from_slice(&rotated);
+        self.camellia.decrypt(&mut block);
+        buf.iter_mut().enumerate().for_each(|(i, b)| {
+            *b = block.bytes[i];
+        });
+        Ok(())
+    }
 }
 
 impl Scheme for MalieScheme {
     fn extract(
         &self,
         file_path: &std::path::PathBuf,
+        options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         crate::archive::NavigableDirectory,
     )> {
-        let camellia =
-            CamelliaCipher::new(&self.get_game_key()?).map_err(|_| {
-                AkaibuError::Custom("Invalid Camellia key length".to_owned())
-            })?;
         let mut buf = vec![0; 16];
         let file = RandomAccessFile::open(file_path)?;
         file.read_exact_at(0, &mut buf)?;
-        decrypt(&mut buf, 0, &camellia)?;
+        let cipher = match self.get_game_key(&buf, &options.malie)? {
+            Some(key) => Some(MalieCipher::new(key)?),
+            None => None,
+        };
+        if let Some(cipher) = &cipher {
+            cipher.decrypt(&mut buf, 0)?;
+        }
 
         let header = buf.pread::<MalieHeader>(0)?;
         log::debug!("Header: {:#?}", header);
@@ -51,12 +192,9 @@ impl Scheme for MalieScheme {
         let file_entries_size = (header.entry_count << 5) as usize;
         let mut buf = vec![0; align_size(size)];
         file.read_exact_at(16, &mut buf)?;
-        buf.chunks_mut(16)
-            .enumerate()
-            .try_for_each::<_, anyhow::Result<()>>(|(i, c)| {
-                decrypt(c, ((i + 1) * 0x10) as u32, &camellia)?;
-                Ok(())
-            })?;
+        if let Some(cipher) = &cipher {
+            decrypt_file(&mut buf, 0x10, cipher)?;
+        }
         buf.resize(size, 0);
         let file_offset_table: Vec<u64> = buf[file_entries_size..]
             .chunks_exact(4)
@@ -113,7 +251,7 @@ impl Scheme for MalieScheme {
             Box::new(MalieArchive {
                 file,
                 archive,
-                camellia,
+                cipher,
                 file_data_offset,
             }),
             navigable_dir,
@@ -126,6 +264,9 @@ impl Scheme for MalieScheme {
             match self {
                 Self::HaruUso => "Haru Uso -Passing Memories-",
                 Self::NatsuUso => "Natsu Uso -Ahead of the Reminiscence-",
+                Self::KeyTable(name) => name,
+                Self::UserKeyTable => "User key tables",
+                Self::Unencrypted => "Unencrypted",
             }
         )
     }
@@ -134,25 +275,57 @@ impl Scheme for MalieScheme {
     where
         Self: Sized,
     {
-        vec![Box::new(Self::HaruUso), Box::new(Self::NatsuUso)]
+        let mut schemes: Vec<Box<dyn Scheme>> =
+            vec![Box::new(Self::HaruUso), Box::new(Self::NatsuUso)];
+        match get_embedded_keys() {
+            Ok(keys) => schemes.extend(
+                keys.keys()
+                    .filter(|name| *name != "HaruUso" && *name != "NatsuUso")
+                    .map(|name| {
+                        Box::new(Self::KeyTable(name.clone()))
+                            as Box<dyn Scheme>
+                    }),
+            ),
+            Err(err) => log::error!("{}", err),
+        }
+        schemes.push(Box::new(Self::UserKeyTable));
+        schemes.push(Box::new(Self::Unencrypted));
+        schemes
     }
 }
 
 impl MalieScheme {
-    fn get_game_key(&self) -> anyhow::Result<Vec<u8>> {
-        let keys: HashMap<String, Vec<u8>> = serde_json::from_slice(
-            &crate::Resources::get(KEYS_PATH).context(format!(
-                "Could not find embedded resource: {}",
-                KEYS_PATH
-            ))?,
-        )?;
-        Ok(keys
-            .get(match self {
-                Self::HaruUso => "HaruUso",
-                Self::NatsuUso => "NatsuUso",
-            })
-            .context("Malie key not found")?
-            .clone())
+    fn get_game_key<'a>(
+        &self,
+        header: &[u8],
+        options: &'a MalieOptions,
+    ) -> anyhow::Result<Option<&'a MalieKey>> {
+        let name = match self {
+            Self::HaruUso => "HaruUso",
+            Self::NatsuUso => "NatsuUso",
+            Self::KeyTable(name) => name,
+            Self::UserKeyTable => {
+                return Ok(Some(
+                    options
+                        .keys
+                        .values()
+                        .find(|key| {
+                            MalieCipher::new(key).map_or(false, |cipher| {
+                                decrypts_header(header, &cipher)
+                            })
+                        })
+                        .context(
+                            "No user provided Malie key matches archive",
+                        )?,
+                ))
+            }
+            Self::Unencrypted => return Ok(None),
+        };
+        Ok(Some(
+            get_embedded_keys()?
+                .get(name)
+                .context("Malie key not found")?,
+        ))
     }
 }
 
@@ -160,7 +333,7 @@ impl MalieScheme {
 struct MalieArchive {
     file: RandomAccessFile,
     archive: Malie,
-    camellia: CamelliaCipher,
+    cipher: Option<MalieCipher>,
     file_data_offset: u64,
 }
 
@@ -228,7 +401,9 @@ impl MalieArchive {
         let mut buf = BytesMut::with_capacity(aligned);
         buf.resize(aligned, 0);
         self.file.read_exact_at(offset as u64, &mut buf)?;
-        decrypt_file(&mut buf, offset, &self.camellia)?;
+        if let Some(cipher) = &self.cipher {
+            decrypt_file(&mut buf, offset, cipher)?;
+        }
         buf.resize(entry.file_size as usize, 0);
         Ok(buf.freeze())
     }
@@ -316,6 +491,8 @@ impl<'a> ctx::TryFromCtx<'a, (usize, &[u64])> for MalieEntry {
     }
 }
 
+/// Even words are rotated left and odd words rotated right by
+/// 16 + bits 4..8 of block offset
 fn rotate_buffer(buf: &[u8], mut n: u32) -> anyhow::Result<Bytes> {
     let mut result = BytesMut::with_capacity(16);
     n >>= 4;
@@ -343,21 +520,6 @@ fn align_size(size: usize) -> usize {
     }
 }
 
-fn decrypt(
-    buf: &mut [u8],
-    n: u32,
-    camellia: &CamelliaCipher,
-) -> anyhow::Result<()> {
-    let rotated = rotate_buffer(buf, n)?;
-    let mut block = Block::default();
-    block.bytes.copy_from_slice(&rotated);
-    camellia.decrypt(&mut block);
-    buf.iter_mut().enumerate().for_each(|(i, b)| {
-        *b = block.bytes[i];
-    });
-    Ok(())
-}
-
 fn get_path(
     id: usize,
     directories: &[(usize, String, std::ops::Range<usize>)],
@@ -379,10 +541,10 @@ fn get_path(
 fn decrypt_file(
     buf: &mut [u8],
     offset: usize,
-    camellia: &CamelliaCipher,
+    cipher: &MalieCipher,
 ) -> anyhow::Result<()> {
     buf.chunks_mut(16).enumerate().try_for_each(|(i, chunk)| {
-        decrypt(chunk, offset as u32 + i as u32 * 16, camellia)?;
+        cipher.decrypt(chunk, offset as u32 + i as u32 * 16)?;
         Ok(())
     })
 }
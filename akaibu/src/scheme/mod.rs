@@ -1,22 +1,28 @@
-use crate::archive;
+use crate::{archive, options::Options};
 use archive::NavigableDirectory;
 use dyn_clone::DynClone;
 use std::{fmt::Debug, path::PathBuf};
 
 pub mod acv1;
 pub mod buriko;
+pub mod cpz6;
 pub mod cpz7;
 pub mod esc_arc2;
 pub mod gxp;
 pub mod malie;
+pub mod nscripter;
+pub mod ovk;
 pub mod pf8;
+pub mod siglus;
 pub mod silky;
+pub mod xp3;
 pub mod ypf;
 
 pub trait Scheme: Debug + Send + DynClone {
     fn extract(
         &self,
         file_path: &PathBuf,
+        options: &Options,
     ) -> anyhow::Result<(Box<dyn archive::Archive + Sync>, NavigableDirectory)>;
     fn get_name(&self) -> String;
     fn get_schemes() -> Vec<Box<dyn Scheme>>
@@ -0,0 +1,442 @@
+use super::Scheme;
+use crate::{archive, error::AkaibuError, options::Options};
+use anyhow::Context;
+use bytes::{Bytes, BytesMut};
+use encoding_rs::SHIFT_JIS;
+use positioned_io::{RandomAccessFile, ReadAt};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scroll::{ctx, Pread, BE};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+const HEADER_SIZE: usize = 6;
+/// Name terminator, offset and size
+const SAR_MIN_ENTRY_SIZE: usize = 2 + 8;
+/// Name terminator, compression, offset, size and original size
+const NSA_MIN_ENTRY_SIZE: usize = 2 + 13;
+const MAX_ENTRY_SIZE: usize = 0x110;
+const BMP_HEADER_SIZE: usize = 54;
+const LZSS_WINDOW_SIZE: usize = 256;
+const LZSS_WINDOW_START: usize = 239;
+
+#[derive(Debug, Clone)]
+pub enum NscripterScheme {
+    Nsa,
+    Sar,
+}
+
+impl Scheme for NscripterScheme {
+    fn extract(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<(
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    )> {
+        let mut buf = vec![0; HEADER_SIZE];
+        let file = RandomAccessFile::open(file_path)?;
+        file.read_exact_at(0, &mut buf)?;
+        let entry_count = buf.pread_with::<u16>(0, BE)?;
+        let base_offset = buf.pread_with::<u32>(2, BE)? as usize;
+        let archive_size = std::fs::metadata(file_path)?.len();
+        if base_offset < HEADER_SIZE || base_offset as u64 > archive_size {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid NScripter archive base offset: {}",
+                base_offset
+            ))
+            .into());
+        }
+
+        buf.resize(base_offset - HEADER_SIZE, 0);
+        file.read_exact_at(HEADER_SIZE as u64, &mut buf)?;
+        let is_nsa = matches!(self, Self::Nsa);
+        let off = &mut 0;
+        let mut file_entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let mut entry =
+                buf.gread_with::<NscripterFileEntry>(off, is_nsa)?;
+            entry.file_offset += base_offset as u64;
+            file_entries.push(entry);
+        }
+        let archive = Nscripter { file_entries };
+        log::debug!("Archive: {:#?}", archive);
+
+        let root_dir = NscripterArchive::new_root_dir(&archive.file_entries);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((Box::new(NscripterArchive { file, archive }), navigable_dir))
+    }
+    fn get_name(&self) -> String {
+        format!(
+            "[NSCRIPTER] {}",
+            match self {
+                Self::Nsa => "NSA",
+                Self::Sar => "SAR",
+            }
+        )
+    }
+    fn get_schemes() -> Vec<Box<dyn Scheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Nsa), Box::new(Self::Sar)]
+    }
+}
+
+/// NSA has no magic so check if header and first entry are consistent.
+/// First entry always starts at base offset.
+pub(crate) fn is_nsa(buf: &[u8]) -> bool {
+    match first_entry_fields(buf, NSA_MIN_ENTRY_SIZE) {
+        Some(off) => {
+            let compression = buf.pread::<u8>(off).unwrap_or(0xFF);
+            let file_offset = buf.pread_with::<u32>(off + 1, BE).ok();
+            let sizes = (
+                buf.pread_with::<u32>(off + 5, BE),
+                buf.pread_with::<u32>(off + 9, BE),
+            );
+            Compression::from_u8(compression).is_some()
+                && file_offset == Some(0)
+                && match sizes {
+                    (Ok(file_size), Ok(original_size)) if compression == 0 => {
+                        file_size == original_size
+                    }
+                    _ => true,
+                }
+        }
+        None => false,
+    }
+}
+
+/// SAR has no magic so check if header and first entry are consistent.
+/// First entry always starts at base offset.
+pub(crate) fn is_sar(buf: &[u8]) -> bool {
+    match first_entry_fields(buf, SAR_MIN_ENTRY_SIZE) {
+        Some(off) => buf.pread_with::<u32>(off, BE).ok() == Some(0),
+        None => false,
+    }
+}
+
+/// Validate header and first file name, returns offset of fields
+/// following the name
+fn first_entry_fields(buf: &[u8], min_entry_size: usize) -> Option<usize> {
+    let entry_count = buf.pread_with::<u16>(0, BE).ok()? as usize;
+    let base_offset = buf.pread_with::<u32>(2, BE).ok()? as usize;
+    if entry_count == 0
+        || base_offset < HEADER_SIZE + entry_count * min_entry_size
+        || base_offset > HEADER_SIZE + entry_count * MAX_ENTRY_SIZE
+    {
+        return None;
+    }
+    let name_length = buf[HEADER_SIZE..].iter().position(|b| *b == 0)?;
+    if name_length == 0
+        || buf[HEADER_SIZE..HEADER_SIZE + name_length]
+            .iter()
+            .any(|b| *b < 0x20)
+    {
+        return None;
+    }
+    Some(HEADER_SIZE + name_length + 1)
+}
+
+#[derive(Debug)]
+struct NscripterArchive {
+    file: RandomAccessFile,
+    archive: Nscripter,
+}
+
+impl archive::Archive for NscripterArchive {
+    fn extract(&self, entry: &archive::FileEntry) -> anyhow::Result<Bytes> {
+        self.archive
+            .file_entries
+            .iter()
+            .find(|e| e.full_path == entry.full_path)
+            .map(|e| self.extract(e))
+            .context("File not found")?
+    }
+
+    fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
+        self.archive.file_entries.par_iter().try_for_each(|entry| {
+            let buf = self.extract(entry)?;
+            let mut output_file_name = PathBuf::from(output_path);
+            output_file_name.push(&entry.full_path);
+            std::fs::create_dir_all(
+                &output_file_name
+                    .parent()
+                    .context("Could not get parent directory")?,
+            )?;
+            log::debug!(
+                "Extracting resource: {:?} {:X?}",
+                output_file_name,
+                entry
+            );
+            File::create(output_file_name)?.write_all(&buf)?;
+            Ok(())
+        })
+    }
+}
+
+impl NscripterArchive {
+    fn new_root_dir(entries: &[NscripterFileEntry]) -> archive::Directory {
+        archive::Directory::new(
+            entries
+                .iter()
+                .map(|entry| {
+                    let file_offset = entry.file_offset;
+                    let file_size = entry.file_size as u64;
+                    archive::FileEntry {
+                        file_name: String::from(
+                            entry
+                                .full_path
+                                .file_name()
+                                .expect("No file name")
+                                .to_str()
+                                .expect("Not valid UTF-8"),
+                        ),
+                        full_path: entry.full_path.clone(),
+                        file_offset,
+                        file_size,
+                    }
+                })
+                .collect(),
+        )
+    }
+    fn extract(&self, entry: &NscripterFileEntry) -> anyhow::Result<Bytes> {
+        let mut buf = BytesMut::with_capacity(entry.file_size as usize);
+        buf.resize(entry.file_size as usize, 0);
+        self.file.read_exact_at(entry.file_offset, &mut buf)?;
+        match entry.compression {
+            Compression::None => Ok(buf.freeze()),
+            Compression::Spb => Ok(Bytes::from(decode_spb(&buf)?)),
+            Compression::Lzss => Ok(Bytes::from(decompress_lzss(
+                &buf,
+                entry.original_size as usize,
+            ))),
+            Compression::Nbz => Ok(Bytes::from(decompress_nbz(&buf)?)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Nscripter {
+    file_entries: Vec<NscripterFileEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compression {
+    None,
+    Spb,
+    Lzss,
+    Nbz,
+}
+
+impl Compression {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Spb),
+            2 => Some(Self::Lzss),
+            4 => Some(Self::Nbz),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NscripterFileEntry {
+    file_offset: u64,
+    file_size: u32,
+    original_size: u32,
+    compression: Compression,
+    full_path: PathBuf,
+}
+
+impl<'a> ctx::TryFromCtx<'a, bool> for NscripterFileEntry {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        is_nsa: bool,
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let name_length = buf
+            .iter()
+            .position(|b| *b == 0)
+            .context("File name not terminated")?;
+        let file_name = SHIFT_JIS.decode(&buf[..name_length]).0.to_string();
+        *off += name_length + 1;
+        let compression = if is_nsa {
+            let compression = buf.gread::<u8>(off)?;
+            Compression::from_u8(compression).ok_or_else(|| {
+                AkaibuError::Unimplemented(format!(
+                    "NSA compression type {}",
+                    compression
+                ))
+            })?
+        } else {
+            Compression::None
+        };
+        let file_offset = buf.gread_with::<u32>(off, BE)? as u64;
+        let file_size = buf.gread_with::<u32>(off, BE)?;
+        let original_size = if is_nsa {
+            buf.gread_with::<u32>(off, BE)?
+        } else {
+            file_size
+        };
+        let mut full_path = PathBuf::from(file_name.replace("\\", "/"));
+        // NBZ entries are bzip2 compressed WAVE files
+        if let Compression::Nbz = compression {
+            full_path.set_extension("wav");
+        }
+        Ok((
+            Self {
+                file_offset,
+                file_size,
+                original_size,
+                compression,
+                full_path,
+            },
+            *off,
+        ))
+    }
+}
+
+/// Original size followed by bzip2 stream
+fn decompress_nbz(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let original_size = buf.pread_with::<u32>(0, BE)? as usize;
+    let mut dest = Vec::new();
+    bzip2_rs::DecoderReader::new(&buf[4..]).read_to_end(&mut dest)?;
+    if dest.len() != original_size {
+        return Err(AkaibuError::Custom(format!(
+            "NBZ size mismatch: expected {} got {}",
+            original_size,
+            dest.len()
+        ))
+        .into());
+    }
+    Ok(dest)
+}
+
+/// LZSS with 256 byte window, flag bit precedes each literal or
+/// 8 bit window position and 4 bit length
+fn decompress_lzss(buf: &[u8], dest_size: usize) -> Vec<u8> {
+    let mut dest = Vec::with_capacity(dest_size);
+    let mut window = [0u8; LZSS_WINDOW_SIZE];
+    let mut window_index = LZSS_WINDOW_START;
+    let mut reader = BitReader::new(buf);
+    while dest.len() < dest_size && !reader.is_empty() {
+        if reader.read(1) == 1 {
+            let b = reader.read(8) as u8;
+            dest.push(b);
+            window[window_index] = b;
+            window_index = (window_index + 1) % LZSS_WINDOW_SIZE;
+        } else {
+            let position = reader.read(8) as usize;
+            let count = reader.read(4) as usize + 2;
+            for i in 0..count {
+                let b = window[(position + i) % LZSS_WINDOW_SIZE];
+                dest.push(b);
+                window[window_index] = b;
+                window_index = (window_index + 1) % LZSS_WINDOW_SIZE;
+            }
+        }
+    }
+    dest.truncate(dest_size);
+    dest
+}
+
+/// SPB stores each BGR channel as delta coded groups of four pixels
+/// traversed in serpentine order from top row, decoded to 24 bit BMP
+fn decode_spb(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let width = buf.pread_with::<u16>(0, BE)? as usize;
+    let height = buf.pread_with::<u16>(2, BE)? as usize;
+    let pixel_count = width * height;
+    let stride = (width * 3 + 3) & !3;
+    let mut bmp = bmp_header(width, height, stride);
+    let pixels_start = bmp.len();
+    bmp.resize(pixels_start + stride * height, 0);
+
+    let mut reader = BitReader::new(&buf[4..]);
+    let mut channel = Vec::with_capacity(pixel_count + 4);
+    for c in 0..3 {
+        channel.clear();
+        let mut value = reader.read(8) as u8;
+        channel.push(value);
+        while channel.len() < pixel_count {
+            let n = reader.read(3);
+            if n == 0 {
+                channel.extend_from_slice(&[value; 4]);
+                continue;
+            }
+            let bits = if n == 7 { reader.read(1) + 1 } else { n + 2 };
+            for _ in 0..4 {
+                if bits == 8 {
+                    value = reader.read(8) as u8;
+                } else {
+                    let k = reader.read(bits);
+                    if k & 1 == 1 {
+                        value = value.wrapping_add((k >> 1) as u8 + 1);
+                    } else {
+                        value = value.wrapping_sub((k >> 1) as u8);
+                    }
+                }
+                channel.push(value);
+            }
+        }
+        // BMP rows are stored bottom up
+        for (y, row) in channel.chunks(width.max(1)).take(height).enumerate() {
+            let row_start = pixels_start + (height - 1 - y) * stride;
+            for (i, value) in row.iter().enumerate() {
+                let x = if y % 2 == 1 { width - 1 - i } else { i };
+                bmp[row_start + x * 3 + c] = *value;
+            }
+        }
+    }
+    Ok(bmp)
+}
+
+fn bmp_header(width: usize, height: usize, stride: usize) -> Vec<u8> {
+    let image_size = (stride * height) as u32;
+    let mut header = Vec::with_capacity(BMP_HEADER_SIZE + image_size as usize);
+    header.extend_from_slice(b"BM");
+    header.extend_from_slice(
+        &(BMP_HEADER_SIZE as u32 + image_size).to_le_bytes(),
+    );
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&(BMP_HEADER_SIZE as u32).to_le_bytes());
+    header.extend_from_slice(&40u32.to_le_bytes());
+    header.extend_from_slice(&(width as i32).to_le_bytes());
+    header.extend_from_slice(&(height as i32).to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&24u16.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&image_size.to_le_bytes());
+    header.extend_from_slice(&[0; 16]);
+    header
+}
+
+/// Most significant bit first reader
+#[derive(Debug)]
+struct BitReader<'a> {
+    buf: &'a [u8],
+    bit_index: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, bit_index: 0 }
+    }
+    fn is_empty(&self) -> bool {
+        self.bit_index >= self.buf.len() * 8
+    }
+    fn read(&mut self, bits: u32) -> u32 {
+        let mut ret = 0;
+        for _ in 0..bits {
+            let byte = self.buf.get(self.bit_index / 8).copied().unwrap_or(0);
+            ret = (ret << 1) | ((byte >> (7 - self.bit_index % 8)) & 1) as u32;
+            self.bit_index += 1;
+        }
+        ret
+    }
+}
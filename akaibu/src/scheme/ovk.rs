@@ -0,0 +1,192 @@
+use super::Scheme;
+use crate::{archive, error::AkaibuError, options::Options};
+use anyhow::Context;
+use bytes::{Bytes, BytesMut};
+use positioned_io::{RandomAccessFile, ReadAt};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scroll::{ctx, Pread, LE};
+use std::{fs::File, io::Write, path::PathBuf};
+
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone)]
+pub enum OvkScheme {
+    Universal,
+}
+
+impl Scheme for OvkScheme {
+    fn extract(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<(
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    )> {
+        let mut buf = vec![0; 4];
+        let file = RandomAccessFile::open(file_path)?;
+        file.read_exact_at(0, &mut buf)?;
+        let entry_count = buf.pread_with::<u32>(0, LE)?;
+        let archive_size = std::fs::metadata(file_path)?.len();
+        if entry_count as u64 * ENTRY_SIZE as u64 + 4 > archive_size {
+            return Err(AkaibuError::Custom(format!(
+                "Invalid OVK entry count: {}",
+                entry_count
+            ))
+            .into());
+        }
+
+        buf.resize(entry_count as usize * ENTRY_SIZE, 0);
+        file.read_exact_at(4, &mut buf)?;
+        let archive_name = file_path
+            .file_stem()
+            .context("Could not get file name")?
+            .to_str()
+            .context("Not valid UTF-8")?;
+        let archive = buf.pread_with::<Ovk>(0, (entry_count, archive_name))?;
+        log::debug!("Archive: {:#?}", archive);
+
+        let root_dir = OvkArchive::new_root_dir(&archive.file_entries);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((Box::new(OvkArchive { file, archive }), navigable_dir))
+    }
+    fn get_name(&self) -> String {
+        format!(
+            "[OVK] {}",
+            match self {
+                Self::Universal => "Universal",
+            }
+        )
+    }
+    fn get_schemes() -> Vec<Box<dyn Scheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+/// OVK has no magic so check if index is followed by data of first entry
+pub(crate) fn is_ovk(buf: &[u8]) -> bool {
+    match (buf.pread_with::<u32>(0, LE), buf.pread_with::<u32>(8, LE)) {
+        (Ok(entry_count), Ok(first_offset)) => {
+            entry_count != 0
+                && entry_count < 0x1000_0000
+                && first_offset == entry_count * ENTRY_SIZE as u32 + 4
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug)]
+struct OvkArchive {
+    file: RandomAccessFile,
+    archive: Ovk,
+}
+
+impl archive::Archive for OvkArchive {
+    fn extract(&self, entry: &archive::FileEntry) -> anyhow::Result<Bytes> {
+        self.archive
+            .file_entries
+            .iter()
+            .find(|e| e.full_path == entry.full_path)
+            .map(|e| self.extract(e))
+            .context("File not found")?
+    }
+
+    fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
+        self.archive.file_entries.par_iter().try_for_each(|entry| {
+            let buf = self.extract(entry)?;
+            let mut output_file_name = PathBuf::from(output_path);
+            output_file_name.push(&entry.full_path);
+            std::fs::create_dir_all(
+                &output_file_name
+                    .parent()
+                    .context("Could not get parent directory")?,
+            )?;
+            log::debug!(
+                "Extracting resource: {:?} {:X?}",
+                output_file_name,
+                entry
+            );
+            File::create(output_file_name)?.write_all(&buf)?;
+            Ok(())
+        })
+    }
+}
+
+impl OvkArchive {
+    fn new_root_dir(entries: &[OvkFileEntry]) -> archive::Directory {
+        archive::Directory::new(
+            entries
+                .iter()
+                .map(|entry| {
+                    let file_offset = entry.file_offset as u64;
+                    let file_size = entry.file_size as u64;
+                    archive::FileEntry {
+                        file_name: String::from(
+                            entry
+                                .full_path
+                                .file_name()
+                                .expect("No file name")
+                                .to_str()
+                                .expect("Not valid UTF-8"),
+                        ),
+                        full_path: entry.full_path.clone(),
+                        file_offset,
+                        file_size,
+                    }
+                })
+                .collect(),
+        )
+    }
+    fn extract(&self, entry: &OvkFileEntry) -> anyhow::Result<Bytes> {
+        let mut buf = BytesMut::with_capacity(entry.file_size as usize);
+        buf.resize(entry.file_size as usize, 0);
+        self.file
+            .read_exact_at(entry.file_offset as u64, &mut buf)?;
+        Ok(buf.freeze())
+    }
+}
+
+#[derive(Debug)]
+struct Ovk {
+    file_entries: Vec<OvkFileEntry>,
+}
+
+impl<'a> ctx::TryFromCtx<'a, (u32, &str)> for Ovk {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        (entry_count, archive_name): (u32, &str),
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let mut file_entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let file_size = buf.gread_with::<u32>(off, LE)?;
+            let file_offset = buf.gread_with::<u32>(off, LE)?;
+            let id = buf.gread_with::<u32>(off, LE)?;
+            let sample_count = buf.gread_with::<u32>(off, LE)?;
+            file_entries.push(OvkFileEntry {
+                file_size,
+                file_offset,
+                id,
+                sample_count,
+                full_path: PathBuf::from(format!(
+                    "{}_{:05}.ogg",
+                    archive_name, id
+                )),
+            });
+        }
+        Ok((Self { file_entries }, *off))
+    }
+}
+
+#[derive(Debug)]
+struct OvkFileEntry {
+    file_size: u32,
+    file_offset: u32,
+    id: u32,
+    sample_count: u32,
+    full_path: PathBuf,
+}
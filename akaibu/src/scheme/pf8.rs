@@ -1,12 +1,18 @@
 use super::Scheme;
-use crate::archive;
+use crate::{archive, error::AkaibuError, options::Options};
 use anyhow::Context;
 use bytes::BytesMut;
+use encoding_rs::SHIFT_JIS;
 use positioned_io::{RandomAccessFile, ReadAt};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use scroll::{ctx, Pread, LE};
 use std::{fs::File, io::Write, path::PathBuf};
 
+/// Versions are stored as ASCII digit after "pf"
+const VERSION_PF2: u8 = b'2';
+const VERSION_PF6: u8 = b'6';
+const VERSION_PF8: u8 = b'8';
+
 #[derive(Debug, Clone)]
 pub enum Pf8Scheme {
     Universal,
@@ -16,6 +22,7 @@ impl Scheme for Pf8Scheme {
     fn extract(
         &self,
         file_path: &PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         archive::NavigableDirectory,
@@ -26,15 +33,27 @@ impl Scheme for Pf8Scheme {
 
         let header = buf.pread::<Pf8Header>(0)?;
         log::debug!("Header: {:#?}", header);
+        if ![VERSION_PF2, VERSION_PF6, VERSION_PF8].contains(&header.version) {
+            return Err(AkaibuError::Unimplemented(format!(
+                "Unsupported Artemis archive version pf{}",
+                header.version as char
+            ))
+            .into());
+        }
 
         let mut buf = vec![0; header.archive_data_size as usize - 4];
         file.read_exact_at(11, &mut buf)?;
         let archive = buf.pread_with::<Pf8>(0, header)?;
         log::debug!("Archive: {:#?}", archive);
 
-        let mut buf = vec![0; header.archive_data_size as usize];
-        file.read_exact_at(7, &mut buf)?;
-        let sha1 = sha1::Sha1::from(&buf).digest().bytes();
+        // Only pf8 is encrypted, with SHA1 of index as key
+        let sha1 = if header.version == VERSION_PF8 {
+            let mut buf = vec![0; header.archive_data_size as usize];
+            file.read_exact_at(7, &mut buf)?;
+            Some(sha1::Sha1::from(&buf).digest().bytes())
+        } else {
+            None
+        };
 
         let root_dir = Pf8Archive::new_root_dir(&archive.file_entries);
         let navigable_dir = archive::NavigableDirectory::new(root_dir);
@@ -66,7 +85,7 @@ impl Scheme for Pf8Scheme {
 #[derive(Debug)]
 struct Pf8Archive {
     file: RandomAccessFile,
-    sha1: [u8; 20],
+    sha1: Option<[u8; 20]>,
     archive: Pf8,
 }
 
@@ -135,15 +154,14 @@ impl Pf8Archive {
 
         self.file
             .read_exact_at(entry.file_offset as u64, &mut buf)?;
-        self.decrypt_file(&mut buf)?;
+        if let Some(sha1) = &self.sha1 {
+            Self::decrypt_file(sha1, &mut buf)?;
+        }
         Ok(buf.freeze())
     }
-    fn decrypt_file(&self, data: &mut [u8]) -> anyhow::Result<()> {
+    fn decrypt_file(sha1: &[u8], data: &mut [u8]) -> anyhow::Result<()> {
         data.iter_mut().enumerate().try_for_each(|(i, b)| {
-            *b ^= self
-                .sha1
-                .get(i % self.sha1.len())
-                .context("Out of bounds access")?;
+            *b ^= sha1.get(i % sha1.len()).context("Out of bounds access")?;
             Ok(())
         })
     }
@@ -165,7 +183,7 @@ impl<'a> ctx::TryFromCtx<'a, Pf8Header> for Pf8 {
         let mut file_entries =
             Vec::with_capacity(header.file_entries_count as usize);
         for _ in 0..header.file_entries_count {
-            file_entries.push(buf.gread(off)?);
+            file_entries.push(buf.gread_with(off, header.version)?);
         }
         Ok((
             Pf8 {
@@ -194,24 +212,30 @@ struct Pf8FileEntry {
     file_size: u32,
 }
 
-impl<'a> ctx::TryFromCtx<'a, ()> for Pf8FileEntry {
+impl<'a> ctx::TryFromCtx<'a, u8> for Pf8FileEntry {
     type Error = anyhow::Error;
     fn try_from_ctx(
         buf: &'a [u8],
-        _: (),
+        version: u8,
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
         let file_name_size = buf.gread_with::<u32>(off, LE)?;
-        let full_path = PathBuf::from(
-            String::from_utf8(
-                buf.get(*off..*off + file_name_size as usize)
-                    .context("Out of bounds access")?
-                    .to_vec(),
-            )?
-            .replace("\\", "/"),
-        );
+        let file_name = buf
+            .get(*off..*off + file_name_size as usize)
+            .context("Out of bounds access")?;
+        // pf2 names are Shift-JIS, later versions UTF-8
+        let file_name = if version == VERSION_PF2 {
+            SHIFT_JIS.decode(file_name).0.to_string()
+        } else {
+            String::from_utf8(file_name.to_vec())?
+        };
+        let full_path = PathBuf::from(file_name.replace("\\", "/"));
         *off += file_name_size as usize;
         let unk = buf.gread_with::<u32>(off, LE)?;
+        // pf2 entries have one more unknown field
+        if version == VERSION_PF2 {
+            *off += 4;
+        }
         let file_offset = buf.gread_with::<u32>(off, LE)?;
         let file_size = buf.gread_with::<u32>(off, LE)?;
         Ok((
@@ -0,0 +1,376 @@
+use super::Scheme;
+use crate::{archive, error::AkaibuError, options::Options};
+use anyhow::Context;
+use bytes::Bytes;
+use positioned_io::{RandomAccessFile, ReadAt};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scroll::{ctx, Pread, LE};
+use std::{fs::File, io::Write, path::PathBuf};
+
+const HEADER_SIZE: usize = 0x5C;
+const GAME_KEY_SIZE: usize = 16;
+/// Table used to obfuscate scene data and Gameexe.dat in all Siglus games
+const EASY_ANGOU_CODE: [u8; 256] = [
+    0x70, 0xF8, 0xA6, 0xB0, 0xA1, 0xA5, 0x28, 0x4F, 0xB5, 0x2F, 0x48, 0xFA,
+    0xE1, 0xE9, 0x4B, 0xDE, 0xB7, 0x4F, 0x62, 0x95, 0x8B, 0xE0, 0x03, 0x80,
+    0xE7, 0xCF, 0x0F, 0x6B, 0x92, 0x01, 0xEB, 0xF8, 0xA2, 0x88, 0xCE, 0x63,
+    0x04, 0x38, 0xD2, 0x6D, 0x8C, 0xD2, 0x88, 0x76, 0xA7, 0x92, 0x71, 0x8F,
+    0x4E, 0xB6, 0x8D, 0x01, 0x79, 0x88, 0x83, 0x0A, 0xF9, 0xE9, 0x2C, 0xDB,
+    0x67, 0xDB, 0x91, 0x14, 0xD5, 0x9A, 0x4E, 0x79, 0x17, 0x23, 0x08, 0x96,
+    0x0E, 0x1D, 0x15, 0xF9, 0xA5, 0xA0, 0x6F, 0x58, 0x17, 0xC8, 0xA9, 0x46,
+    0xDA, 0x22, 0xFF, 0xFD, 0x87, 0x12, 0x42, 0xFB, 0xA9, 0xB8, 0x67, 0x6C,
+    0x91, 0x67, 0x64, 0xF9, 0xD1, 0x1E, 0xE4, 0x50, 0x64, 0x6F, 0xF2, 0x0B,
+    0xDE, 0x40, 0xE7, 0x47, 0xF1, 0x03, 0xCC, 0x2A, 0xAD, 0x7F, 0x34, 0x21,
+    0xA0, 0x64, 0x26, 0x98, 0x6C, 0xED, 0x69, 0xF4, 0xB5, 0x23, 0x08, 0x6E,
+    0x7D, 0x92, 0xF6, 0xEB, 0x93, 0xF0, 0x7A, 0x89, 0x5E, 0xF9, 0xF8, 0x7A,
+    0xAF, 0xE8, 0xA9, 0x48, 0xC2, 0xAC, 0x11, 0x6B, 0x2B, 0x33, 0xA7, 0x40,
+    0x0D, 0xDC, 0x7D, 0xA7, 0x5B, 0xCF, 0xC8, 0x31, 0xD1, 0x77, 0x52, 0x8D,
+    0xE6, 0x76, 0xCB, 0x9F, 0x8C, 0x1A, 0x1C, 0xE7, 0x4D, 0xCE, 0x7C, 0x2C,
+    0xF5, 0x3D, 0x2F, 0x34, 0xD1, 0x59, 0x02, 0xAA, 0x4A, 0x1F, 0xEB, 0x5F,
+    0x5C, 0x7F, 0x9E, 0x82, 0x14, 0x0C, 0x51, 0xA7, 0x11, 0x2C, 0xA5, 0x74,
+    0xB6, 0x59, 0xA0, 0x6A, 0xB8, 0xC8, 0x78, 0xC6, 0xAC, 0x79, 0x81, 0xF7,
+    0xFE, 0x01, 0xEB, 0xA0, 0xA6, 0xB7, 0x5A, 0x86, 0xE1, 0xE5, 0x29, 0x57,
+    0x31, 0x9D, 0x45, 0x30, 0x3D, 0x1D, 0x9E, 0x86, 0xBA, 0xCA, 0xB8, 0xA8,
+    0x5F, 0x6C, 0x3F, 0x7E, 0x35, 0xD5, 0x07, 0xF9, 0xBD, 0x6D, 0x59, 0x72,
+    0x3C, 0xB4, 0x1C, 0x6E,
+];
+
+/// Options shared by Siglus scheme and resources
+#[derive(Debug, Clone, Default)]
+pub struct SiglusOptions {
+    /// Second layer key stored in game executable.
+    /// Required only by games with `exe_angou_mode` set.
+    game_key: Option<[u8; GAME_KEY_SIZE]>,
+}
+
+impl SiglusOptions {
+    /// Create options with optional game key given as 32 hex digits
+    pub fn new(game_key: Option<&str>) -> anyhow::Result<Self> {
+        Ok(Self {
+            game_key: game_key.map(parse_game_key).transpose()?,
+        })
+    }
+
+    /// Get game key if file is encrypted with it
+    pub(crate) fn get_game_key(
+        &self,
+        exe_angou_mode: u32,
+    ) -> anyhow::Result<Option<[u8; GAME_KEY_SIZE]>> {
+        if exe_angou_mode == 0 {
+            return Ok(None);
+        }
+        match self.game_key {
+            Some(key) => Ok(Some(key)),
+            None => Err(AkaibuError::Custom(
+                "File is encrypted with game specific key, please provide it"
+                    .to_owned(),
+            )
+            .into()),
+        }
+    }
+}
+
+fn parse_game_key(key: &str) -> anyhow::Result<[u8; GAME_KEY_SIZE]> {
+    let key = key
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect::<String>()
+        .replace("0x", "");
+    if key.len() != GAME_KEY_SIZE * 2 {
+        return Err(AkaibuError::Custom(format!(
+            "Siglus game key must have {} bytes",
+            GAME_KEY_SIZE
+        ))
+        .into());
+    }
+    let mut ret = [0; GAME_KEY_SIZE];
+    for (i, b) in ret.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16).map_err(|_| {
+            AkaibuError::Custom(format!("Invalid Siglus game key: {}", key))
+        })?;
+    }
+    Ok(ret)
+}
+
+/// Remove both obfuscation layers and decompress data.
+/// Used by scene data and Gameexe.dat.
+pub(crate) fn decrypt_and_decompress(
+    buf: &mut [u8],
+    game_key: Option<&[u8; GAME_KEY_SIZE]>,
+) -> anyhow::Result<Vec<u8>> {
+    if let Some(game_key) = game_key {
+        buf.iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b ^= game_key[i % GAME_KEY_SIZE]);
+    }
+    buf.iter_mut()
+        .enumerate()
+        .for_each(|(i, b)| *b ^= EASY_ANGOU_CODE[i % EASY_ANGOU_CODE.len()]);
+
+    let compressed_size = buf.pread_with::<u32>(0, LE)? as usize;
+    let uncompressed_size = buf.pread_with::<u32>(4, LE)? as usize;
+    if compressed_size != buf.len() {
+        return Err(AkaibuError::Custom(format!(
+            "Invalid compressed size {:X} expected {:X}{}",
+            compressed_size,
+            buf.len(),
+            if game_key.is_some() {
+                ", wrong game key?"
+            } else {
+                ""
+            }
+        ))
+        .into());
+    }
+    lzss_decompress(&buf[8..], uncompressed_size)
+}
+
+/// LZSS variant shared by most Siglus formats
+pub(crate) fn lzss_decompress(
+    src: &[u8],
+    dest_len: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut dest = Vec::with_capacity(dest_len);
+    let src_index = &mut 0;
+    while dest.len() < dest_len {
+        let mut flags = src.gread::<u8>(src_index)?;
+        for _ in 0..8 {
+            if dest.len() == dest_len {
+                break;
+            }
+            if flags & 1 == 0 {
+                let a = src.gread_with::<u16>(src_index, LE)? as usize;
+                let count = (a & 0x0F) + 2;
+                let mut back_index = dest
+                    .len()
+                    .checked_sub(a >> 4)
+                    .context("Invalid back reference")?;
+                for _ in 0..count {
+                    dest.push(dest.gread::<u8>(&mut back_index)?);
+                }
+            } else {
+                dest.push(src.gread::<u8>(src_index)?);
+            }
+            flags >>= 1;
+        }
+    }
+    dest.truncate(dest_len);
+    Ok(dest)
+}
+
+#[derive(Debug, Clone)]
+pub enum SiglusScheme {
+    Universal,
+}
+
+impl Scheme for SiglusScheme {
+    fn extract(
+        &self,
+        file_path: &PathBuf,
+        options: &Options,
+    ) -> anyhow::Result<(
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    )> {
+        let mut buf = vec![0; HEADER_SIZE];
+        let file = RandomAccessFile::open(file_path)?;
+        file.read_exact_at(0, &mut buf)?;
+        let header = buf.pread::<ScenePckHeader>(0)?;
+        log::debug!("Header: {:#?}", header);
+
+        buf.resize(header.scene_data_list_offset as usize, 0);
+        file.read_exact_at(0, &mut buf)?;
+        let archive = buf.pread_with::<ScenePck>(0, header)?;
+        log::debug!("Archive: {:#?}", archive);
+        let game_key =
+            options.siglus.get_game_key(archive.header.exe_angou_mode)?;
+
+        let root_dir = SiglusArchive::new_root_dir(&archive);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((
+            Box::new(SiglusArchive {
+                file,
+                archive,
+                game_key,
+            }),
+            navigable_dir,
+        ))
+    }
+    fn get_name(&self) -> String {
+        format!(
+            "[SIGLUS] {}",
+            match self {
+                Self::Universal => "Scene.pck",
+            }
+        )
+    }
+    fn get_schemes() -> Vec<Box<dyn Scheme>>
+    where
+        Self: Sized,
+    {
+        vec![Box::new(Self::Universal)]
+    }
+}
+
+#[derive(Debug)]
+struct SiglusArchive {
+    file: RandomAccessFile,
+    archive: ScenePck,
+    game_key: Option<[u8; GAME_KEY_SIZE]>,
+}
+
+impl archive::Archive for SiglusArchive {
+    fn extract(&self, entry: &archive::FileEntry) -> anyhow::Result<Bytes> {
+        self.archive
+            .scenes
+            .iter()
+            .find(|e| e.full_path == entry.full_path)
+            .map(|e| self.extract(e))
+            .context("File not found")?
+    }
+
+    fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
+        self.archive.scenes.par_iter().try_for_each(|entry| {
+            let buf = self.extract(entry)?;
+            let mut output_file_name = PathBuf::from(output_path);
+            output_file_name.push(&entry.full_path);
+            std::fs::create_dir_all(
+                &output_file_name
+                    .parent()
+                    .context("Could not get parent directory")?,
+            )?;
+            log::debug!(
+                "Extracting resource: {:?} {:X?}",
+                output_file_name,
+                entry
+            );
+            File::create(output_file_name)?.write_all(&buf)?;
+            Ok(())
+        })
+    }
+}
+
+impl SiglusArchive {
+    fn new_root_dir(archive: &ScenePck) -> archive::Directory {
+        archive::Directory::new(
+            archive
+                .scenes
+                .iter()
+                .map(|entry| {
+                    let file_offset = archive.header.scene_data_list_offset
+                        as u64
+                        + entry.offset as u64;
+                    let file_size = entry.size as u64;
+                    archive::FileEntry {
+                        file_name: String::from(
+                            entry
+                                .full_path
+                                .file_name()
+                                .expect("No file name")
+                                .to_str()
+                                .expect("Not valid UTF-8"),
+                        ),
+                        full_path: entry.full_path.clone(),
+                        file_offset,
+                        file_size,
+                    }
+                })
+                .collect(),
+        )
+    }
+    fn extract(&self, entry: &SceneEntry) -> anyhow::Result<Bytes> {
+        let mut buf = vec![0; entry.size as usize];
+        self.file.read_exact_at(
+            self.archive.header.scene_data_list_offset as u64
+                + entry.offset as u64,
+            &mut buf,
+        )?;
+        Ok(Bytes::from(decrypt_and_decompress(
+            &mut buf,
+            self.game_key.as_ref(),
+        )?))
+    }
+}
+
+#[derive(Debug)]
+struct ScenePck {
+    header: ScenePckHeader,
+    scenes: Vec<SceneEntry>,
+}
+
+impl<'a> ctx::TryFromCtx<'a, ScenePckHeader> for ScenePck {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        header: ScenePckHeader,
+    ) -> Result<(Self, usize), Self::Error> {
+        if header.scene_name_index_count != header.scene_data_index_count {
+            return Err(AkaibuError::Custom(format!(
+                "Scene name count {} does not match scene data count {}",
+                header.scene_name_index_count, header.scene_data_index_count
+            ))
+            .into());
+        }
+        let name_index_off =
+            &mut (header.scene_name_index_list_offset as usize);
+        let data_index_off =
+            &mut (header.scene_data_index_list_offset as usize);
+        let mut scenes =
+            Vec::with_capacity(header.scene_data_index_count as usize);
+        for _ in 0..header.scene_data_index_count {
+            let name_offset = buf.gread_with::<u32>(name_index_off, LE)?;
+            let name_len = buf.gread_with::<u32>(name_index_off, LE)?;
+            let name_off = &mut (header.scene_name_list_offset as usize
+                + name_offset as usize * 2);
+            let mut name = Vec::with_capacity(name_len as usize);
+            for _ in 0..name_len {
+                name.push(buf.gread_with::<u16>(name_off, LE)?);
+            }
+            let offset = buf.gread_with::<u32>(data_index_off, LE)?;
+            let size = buf.gread_with::<u32>(data_index_off, LE)?;
+            scenes.push(SceneEntry {
+                full_path: PathBuf::from(format!(
+                    "{}.ss",
+                    String::from_utf16_lossy(&name)
+                )),
+                offset,
+                size,
+            });
+        }
+        Ok((Self { header, scenes }, buf.len()))
+    }
+}
+
+#[derive(Debug, Pread, Copy, Clone)]
+struct ScenePckHeader {
+    header_size: u32,
+    inc_prop_list_offset: u32,
+    inc_prop_count: u32,
+    inc_prop_name_index_list_offset: u32,
+    inc_prop_name_index_count: u32,
+    inc_prop_name_list_offset: u32,
+    inc_prop_name_count: u32,
+    inc_cmd_list_offset: u32,
+    inc_cmd_count: u32,
+    inc_cmd_name_index_list_offset: u32,
+    inc_cmd_name_index_count: u32,
+    inc_cmd_name_list_offset: u32,
+    inc_cmd_name_count: u32,
+    scene_name_index_list_offset: u32,
+    scene_name_index_count: u32,
+    scene_name_list_offset: u32,
+    scene_name_count: u32,
+    scene_data_index_list_offset: u32,
+    scene_data_index_count: u32,
+    scene_data_list_offset: u32,
+    scene_data_count: u32,
+    exe_angou_mode: u32,
+    original_source_header_size: u32,
+}
+
+#[derive(Debug)]
+struct SceneEntry {
+    full_path: PathBuf,
+    offset: u32,
+    size: u32,
+}
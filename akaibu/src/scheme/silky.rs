@@ -1,4 +1,4 @@
-use crate::archive;
+use crate::{archive, options::Options};
 
 use super::Scheme;
 use anyhow::Context;
@@ -18,6 +18,7 @@ impl Scheme for SilkyScheme {
     fn extract(
         &self,
         file_path: &PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         crate::archive::NavigableDirectory,
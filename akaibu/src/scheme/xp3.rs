@@ -0,0 +1,365 @@
+use super::Scheme;
+use crate::{
+    archive, error::AkaibuError, options::Options, util::zlib_decompress,
+};
+use anyhow::Context;
+use bytes::{BufMut, Bytes, BytesMut};
+use positioned_io::{RandomAccessFile, ReadAt};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scroll::{ctx, Pread, LE};
+use std::{fs::File, io::Write, path::PathBuf};
+
+const MAGIC_SIZE: usize = 11;
+const INDEX_ENCODE_METHOD_MASK: u8 = 0x07;
+const INDEX_ENCODE_RAW: u8 = 0;
+const INDEX_ENCODE_ZLIB: u8 = 1;
+/// Cushion index found in archives created by newer versions of KiriKiri
+const INDEX_CONTINUE: u8 = 0x80;
+const SEGMENT_ENCODE_ZLIB: u32 = 1;
+const SEGMENT_SIZE: usize = 28;
+
+#[derive(Debug, Clone)]
+pub enum Xp3Scheme {
+    Universal,
+    HashXor,
+    FateStayNight,
+}
+
+impl Scheme for Xp3Scheme {
+    fn extract(
+        &self,
+        file_path: &PathBuf,
+        _options: &Options,
+    ) -> anyhow::Result<(
+        Box<dyn archive::Archive + Sync>,
+        archive::NavigableDirectory,
+    )> {
+        let file = RandomAccessFile::open(file_path)?;
+        let archive_size = std::fs::metadata(file_path)?.len();
+        let index = read_index(&file, archive_size)?;
+        let archive = index.pread::<Xp3>(0)?;
+        log::debug!("Archive: {:#?}", archive);
+
+        let root_dir = Xp3Archive::new_root_dir(&archive.file_entries);
+        let navigable_dir = archive::NavigableDirectory::new(root_dir);
+        Ok((
+            Box::new(Xp3Archive {
+                file,
+                scheme: self.clone(),
+                archive,
+                archive_size,
+            }),
+            navigable_dir,
+        ))
+    }
+    fn get_name(&self) -> String {
+        format!(
+            "[XP3] {}",
+            match self {
+                Self::Universal => "Universal (no encryption)",
+                Self::HashXor => "XOR with file hash",
+                Self::FateStayNight => "Fate/stay night",
+            }
+        )
+    }
+    fn get_schemes() -> Vec<Box<dyn Scheme>>
+    where
+        Self: Sized,
+    {
+        vec![
+            Box::new(Self::Universal),
+            Box::new(Self::HashXor),
+            Box::new(Self::FateStayNight),
+        ]
+    }
+}
+
+impl Xp3Scheme {
+    /// Per game filter applied to file contents after decompression.
+    /// `offset` is position of first byte of `buf` in file.
+    fn decrypt(&self, buf: &mut [u8], hash: u32, offset: u64) {
+        match self {
+            Self::Universal => (),
+            Self::HashXor => buf.iter_mut().for_each(|b| *b ^= hash as u8),
+            Self::FateStayNight => {
+                buf.iter_mut().enumerate().for_each(|(i, b)| {
+                    *b ^= 0x36;
+                    match offset + i as u64 {
+                        0x13 => *b ^= 0x01,
+                        0x2EA29 => *b ^= 0x03,
+                        _ => (),
+                    }
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Xp3Archive {
+    file: RandomAccessFile,
+    scheme: Xp3Scheme,
+    archive: Xp3,
+    archive_size: u64,
+}
+
+impl archive::Archive for Xp3Archive {
+    fn extract(&self, entry: &archive::FileEntry) -> anyhow::Result<Bytes> {
+        self.archive
+            .file_entries
+            .iter()
+            .find(|e| e.full_path == entry.full_path)
+            .map(|e| self.extract(e))
+            .context("File not found")?
+    }
+
+    fn extract_all(&self, output_path: &PathBuf) -> anyhow::Result<()> {
+        self.archive.file_entries.par_iter().try_for_each(|entry| {
+            let buf = self.extract(entry)?;
+            let mut output_file_name = PathBuf::from(output_path);
+            output_file_name.push(&entry.full_path);
+            std::fs::create_dir_all(
+                &output_file_name
+                    .parent()
+                    .context("Could not get parent directory")?,
+            )?;
+            log::debug!(
+                "Extracting resource: {:?} {:X?}",
+                output_file_name,
+                entry
+            );
+            File::create(output_file_name)?.write_all(&buf)?;
+            Ok(())
+        })
+    }
+}
+
+impl Xp3Archive {
+    fn new_root_dir(entries: &[Xp3FileEntry]) -> archive::Directory {
+        archive::Directory::new(
+            entries
+                .iter()
+                .map(|entry| {
+                    let file_offset = entry
+                        .segments
+                        .first()
+                        .map(|segment| segment.offset)
+                        .unwrap_or(0);
+                    let file_size = entry.file_size;
+                    archive::FileEntry {
+                        file_name: String::from(
+                            entry
+                                .full_path
+                                .file_name()
+                                .expect("No file name")
+                                .to_str()
+                                .expect("Not valid UTF-8"),
+                        ),
+                        full_path: entry.full_path.clone(),
+                        file_offset,
+                        file_size,
+                    }
+                })
+                .collect(),
+        )
+    }
+    fn extract(&self, entry: &Xp3FileEntry) -> anyhow::Result<Bytes> {
+        // Sizes come from archive index, so buffer grows only with data
+        // which was actually read
+        let mut result = BytesMut::new();
+        for segment in entry.segments.iter() {
+            check_range(
+                segment.offset,
+                segment.packed_size,
+                self.archive_size,
+            )?;
+            let mut buf = vec![0; segment.packed_size as usize];
+            self.file.read_exact_at(segment.offset, &mut buf)?;
+            if segment.flags & SEGMENT_ENCODE_ZLIB != 0 {
+                buf = zlib_decompress(&buf)?;
+            }
+            result.put_slice(&buf);
+        }
+        self.scheme.decrypt(&mut result, entry.hash, 0);
+        Ok(result.freeze())
+    }
+}
+
+/// Read and decompress archive index, skipping cushion indexes
+fn read_index(
+    file: &RandomAccessFile,
+    archive_size: u64,
+) -> anyhow::Result<Vec<u8>> {
+    let mut buf = vec![0; 8];
+    file.read_exact_at(MAGIC_SIZE as u64, &mut buf)?;
+    let mut index_offset = buf.pread_with::<u64>(0, LE)?;
+    loop {
+        let mut buf = vec![0; 17];
+        file.read_exact_at(index_offset, &mut buf)?;
+        let index_flag = buf.pread::<u8>(0)?;
+        if index_flag == INDEX_CONTINUE {
+            index_offset = buf.pread_with::<u64>(9, LE)?;
+            continue;
+        }
+        return match index_flag & INDEX_ENCODE_METHOD_MASK {
+            INDEX_ENCODE_RAW => {
+                let index_size = buf.pread_with::<u64>(1, LE)?;
+                check_range(index_offset + 9, index_size, archive_size)?;
+                let mut index = vec![0; index_size as usize];
+                file.read_exact_at(index_offset + 9, &mut index)?;
+                Ok(index)
+            }
+            INDEX_ENCODE_ZLIB => {
+                let packed_size = buf.pread_with::<u64>(1, LE)?;
+                check_range(index_offset + 17, packed_size, archive_size)?;
+                let mut index = vec![0; packed_size as usize];
+                file.read_exact_at(index_offset + 17, &mut index)?;
+                zlib_decompress(&index)
+            }
+            method => Err(AkaibuError::Unimplemented(format!(
+                "XP3 index encode method: {} is not supported",
+                method
+            ))
+            .into()),
+        };
+    }
+}
+
+/// Make sure data read from archive lies inside of it before allocating
+/// buffer for it
+fn check_range(
+    offset: u64,
+    size: u64,
+    archive_size: u64,
+) -> anyhow::Result<()> {
+    match offset.checked_add(size) {
+        Some(end) if end <= archive_size => Ok(()),
+        _ => Err(AkaibuError::Custom(format!(
+            "XP3 data at {:#X} with size {:#X} exceeds archive size {:#X}",
+            offset, size, archive_size
+        ))
+        .into()),
+    }
+}
+
+#[derive(Debug)]
+struct Xp3 {
+    file_entries: Vec<Xp3FileEntry>,
+}
+
+impl<'a> ctx::TryFromCtx<'a, ()> for Xp3 {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        _: (),
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let mut file_entries = Vec::new();
+        while *off < buf.len() {
+            let chunk = buf.gread::<Chunk<'_>>(off)?;
+            if chunk.name == b"File" {
+                file_entries.push(chunk.data.pread(0)?);
+            } else {
+                log::debug!("Skipping index chunk: {:X?}", chunk.name);
+            }
+        }
+        Ok((Self { file_entries }, *off))
+    }
+}
+
+#[derive(Debug)]
+struct Chunk<'a> {
+    name: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> ctx::TryFromCtx<'a, ()> for Chunk<'a> {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        _: (),
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let name = buf.get(0..4).context("Out of bounds access")?;
+        *off += name.len();
+        let size = buf.gread_with::<u64>(off, LE)?;
+        let end = (*off as u64)
+            .checked_add(size)
+            .filter(|end| *end <= buf.len() as u64)
+            .context("Out of bounds access")? as usize;
+        let data = &buf[*off..end];
+        *off = end;
+        Ok((Self { name, data }, *off))
+    }
+}
+
+#[derive(Debug)]
+struct Xp3FileEntry {
+    file_size: u64,
+    full_path: PathBuf,
+    segments: Vec<Xp3Segment>,
+    hash: u32,
+}
+
+impl<'a> ctx::TryFromCtx<'a, ()> for Xp3FileEntry {
+    type Error = anyhow::Error;
+    fn try_from_ctx(
+        buf: &'a [u8],
+        _: (),
+    ) -> Result<(Self, usize), Self::Error> {
+        let off = &mut 0;
+        let mut info = None;
+        let mut segments = Vec::new();
+        let mut hash = 0;
+        while *off < buf.len() {
+            let chunk = buf.gread::<Chunk<'_>>(off)?;
+            match chunk.name {
+                b"info" => {
+                    let off = &mut 0;
+                    // Skip flags
+                    *off += 4;
+                    let file_size = chunk.data.gread_with::<u64>(off, LE)?;
+                    // Skip packed file size
+                    *off += 8;
+                    let name_len = chunk.data.gread_with::<u16>(off, LE)?;
+                    let mut name = Vec::with_capacity(name_len as usize);
+                    for _ in 0..name_len {
+                        name.push(chunk.data.gread_with::<u16>(off, LE)?);
+                    }
+                    let full_path = PathBuf::from(
+                        String::from_utf16_lossy(&name).replace("\\", "/"),
+                    );
+                    info = Some((file_size, full_path));
+                }
+                b"segm" => {
+                    for segment in chunk.data.chunks_exact(SEGMENT_SIZE) {
+                        segments.push(segment.pread_with(0, LE)?);
+                    }
+                }
+                b"adlr" => {
+                    hash = chunk.data.pread_with::<u32>(0, LE)?;
+                }
+                _ => log::debug!("Skipping file chunk: {:X?}", chunk.name),
+            }
+        }
+        let (file_size, full_path) =
+            info.context("File entry without info chunk")?;
+        Ok((
+            Self {
+                file_size,
+                full_path,
+                segments,
+                hash,
+            },
+            *off,
+        ))
+    }
+}
+
+#[derive(Debug, Pread)]
+struct Xp3Segment {
+    flags: u32,
+    offset: u64,
+    _original_size: u64,
+    packed_size: u64,
+}
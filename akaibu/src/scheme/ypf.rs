@@ -1,9 +1,10 @@
 use crate::util::zlib_decompress;
-use crate::{archive, error::AkaibuError, scheme::Scheme};
+use crate::{archive, error::AkaibuError, options::Options, scheme::Scheme};
 use anyhow::Context;
 use bytes::Bytes;
 use bytes::BytesMut;
 use encoding_rs::SHIFT_JIS;
+use itertools::Itertools;
 use positioned_io::{RandomAccessFile, ReadAt};
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
@@ -12,6 +13,9 @@ use std::fs::File;
 use std::io::Write;
 use std::{collections::HashMap, path::PathBuf};
 
+/// Size of file entry with empty file name
+const YPF_MIN_ENTRY_SIZE: usize = 23;
+
 #[derive(Debug, Clone)]
 pub enum YpfScheme {
     Universal,
@@ -21,6 +25,7 @@ impl Scheme for YpfScheme {
     fn extract(
         &self,
         file_path: &std::path::PathBuf,
+        _options: &Options,
     ) -> anyhow::Result<(
         Box<dyn crate::archive::Archive + Sync>,
         crate::archive::NavigableDirectory,
@@ -32,13 +37,31 @@ impl Scheme for YpfScheme {
         let header = buf.pread::<YpfHeader>(0)?;
         log::debug!("Header: {:#?}", header);
 
-        let decrypt_name_table =
-            get_decrypt_name_table(header.archive_version)?;
-
         let mut buf = vec![0; header.entry_data_size as usize];
         file.read_exact_at(32, &mut buf)?;
-        let archive =
-            buf.pread_with::<Ypf>(0, (header, &decrypt_name_table))?;
+        let decrypt_name_tables = get_decrypt_name_tables()?;
+        let archive = match decrypt_name_tables.get(&header.archive_version) {
+            Some(decrypt_name_table) => buf.pread_with::<Ypf>(
+                0,
+                (
+                    header,
+                    decrypt_name_table.as_slice(),
+                    get_name_xor_key(header.archive_version),
+                ),
+            )?,
+            None => {
+                log::warn!(
+                    "Unknown YPF archive version: {}, trying to derive name decryption",
+                    header.archive_version
+                );
+                derive_name_decryption(
+                    &buf,
+                    header,
+                    &decrypt_name_tables,
+                    std::fs::metadata(file_path)?.len(),
+                )?
+            }
+        };
         log::debug!("Archive: {:#?}", archive);
 
         let root_dir = YpfArchive::new_root_dir(&archive.file_entries);
@@ -151,18 +174,42 @@ struct Ypf {
     file_entries: Vec<YpfFileEntry>,
 }
 
-impl<'a> ctx::TryFromCtx<'a, (YpfHeader, &'a [u8])> for Ypf {
+impl Ypf {
+    /// Index decrypted with wrong name table or xor key contains garbage
+    fn is_valid(&self, archive_size: u64) -> bool {
+        self.file_entries.iter().all(|entry| {
+            let stored_size = if entry.flags == 1 {
+                entry.compressed_file_size
+            } else {
+                entry.file_size
+            };
+            let full_path = entry.full_path.to_string_lossy();
+            entry.flags <= 1
+                && entry
+                    .file_offset
+                    .checked_add(stored_size as u64)
+                    .map_or(false, |end| end <= archive_size)
+                && !full_path.is_empty()
+                && !full_path.contains('\u{FFFD}')
+                && !full_path.chars().any(char::is_control)
+        })
+    }
+}
+
+impl<'a> ctx::TryFromCtx<'a, (YpfHeader, &'a [u8], u8)> for Ypf {
     type Error = anyhow::Error;
 
     fn try_from_ctx(
         buf: &'a [u8],
-        (header, decrypt_name_table): (YpfHeader, &'a [u8]),
+        (header, decrypt_name_table, name_xor_key): (YpfHeader, &'a [u8], u8),
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
-        let mut file_entries = Vec::with_capacity(header.entry_count as usize);
+        let mut file_entries = Vec::with_capacity(
+            (header.entry_count as usize).min(buf.len() / YPF_MIN_ENTRY_SIZE),
+        );
         for _ in 0..header.entry_count {
             file_entries
-                .push(buf.gread_with(off, (&header, decrypt_name_table))?);
+                .push(buf.gread_with(off, (decrypt_name_table, name_xor_key))?);
         }
         Ok((
             Self {
@@ -196,12 +243,12 @@ struct YpfFileEntry {
     unk2: u32,
 }
 
-impl<'a> ctx::TryFromCtx<'a, (&'a YpfHeader, &'a [u8])> for YpfFileEntry {
+impl<'a> ctx::TryFromCtx<'a, (&'a [u8], u8)> for YpfFileEntry {
     type Error = anyhow::Error;
 
     fn try_from_ctx(
         buf: &'a [u8],
-        (header, decrypt_name_table): (&'a YpfHeader, &'a [u8]),
+        (decrypt_name_table, name_xor_key): (&'a [u8], u8),
     ) -> Result<(Self, usize), Self::Error> {
         let off = &mut 0;
         let unk0 = buf.gread_with::<u32>(off, LE)?;
@@ -210,7 +257,7 @@ impl<'a> ctx::TryFromCtx<'a, (&'a YpfHeader, &'a [u8])> for YpfFileEntry {
         let full_path = decrypt_file_name(
             &buf.get(*off..*off + name_size)
                 .context("Out of bounds access")?,
-            &header,
+            name_xor_key,
         );
         *off += name_size;
         let unk1 = buf.gread_with::<u8>(off, LE)?;
@@ -246,28 +293,86 @@ fn get_name_size(
         .context("Out of bounds context")? as usize)
 }
 
-fn get_decrypt_name_table(archive_version: u32) -> anyhow::Result<Vec<u8>> {
-    let decrypt_name_tables: HashMap<u32, Vec<u8>> = serde_json::from_slice(
+fn get_decrypt_name_tables() -> anyhow::Result<HashMap<u32, Vec<u8>>> {
+    Ok(serde_json::from_slice(
         &crate::Resources::get("ypf/decrypt_name_tables.json").context(
             format!("Could not find file: {}", "ypf/decrypt_name_tables.json"),
         )?,
-    )?;
-    Ok(match decrypt_name_tables.get(&archive_version) {
-        Some(table) => table.clone(),
-        None => {
-            return Err(AkaibuError::Unimplemented(format!(
-                "Unsupported YPF archive version: {}",
-                archive_version
-            ))
-            .into())
-        }
-    })
+    )?)
+}
+
+fn get_name_xor_key(archive_version: u32) -> u8 {
+    if archive_version == 500 {
+        0x36
+    } else {
+        0
+    }
 }
 
-fn decrypt_file_name(buf: &[u8], header: &YpfHeader) -> PathBuf {
-    let mut result: Vec<u8> = buf.iter().map(|b| !b).collect();
-    if header.archive_version == 500 {
-        result.iter_mut().for_each(|b| *b ^= 0x36);
+/// Find name table and xor key for archive version not present in
+/// ypf/decrypt_name_tables.json by checking which of candidates produce consistent index.
+/// Name tables of known versions are tried starting from the closest version,
+/// xor key is additionally guessed from file extension dot of the first entry.
+fn derive_name_decryption(
+    buf: &[u8],
+    header: YpfHeader,
+    decrypt_name_tables: &HashMap<u32, Vec<u8>>,
+    archive_size: u64,
+) -> anyhow::Result<Ypf> {
+    let identity_table = (0..=255).collect::<Vec<u8>>();
+    let candidate_tables = decrypt_name_tables
+        .iter()
+        // Lower version wins between equally distant tables, HashMap
+        // iteration order is not stable
+        .sorted_by_key(|(version, _)| {
+            (
+                (**version as i64 - header.archive_version as i64).abs(),
+                **version,
+            )
+        })
+        .map(|(_, table)| table)
+        .chain(std::iter::once(&identity_table))
+        .unique();
+    for decrypt_name_table in candidate_tables {
+        let candidate_xor_keys = guess_name_xor_key(buf, decrypt_name_table)
+            .into_iter()
+            .chain(vec![0x36, 0])
+            .unique();
+        for name_xor_key in candidate_xor_keys {
+            match buf.pread_with::<Ypf>(
+                0,
+                (header, decrypt_name_table.as_slice(), name_xor_key),
+            ) {
+                Ok(archive) if archive.is_valid(archive_size) => {
+                    log::info!(
+                        "Derived YPF name decryption, table: {:?} xor key: {:#X}",
+                        decrypt_name_table,
+                        name_xor_key
+                    );
+                    return Ok(archive);
+                }
+                _ => continue,
+            }
+        }
     }
+    Err(AkaibuError::Unimplemented(format!(
+        "Unsupported YPF archive version: {}, could not derive name decryption",
+        header.archive_version
+    ))
+    .into())
+}
+
+/// Most file names end with 3 character extension so xor key can be recovered
+/// from position of extension dot
+fn guess_name_xor_key(buf: &[u8], decrypt_name_table: &[u8]) -> Option<u8> {
+    let name_size =
+        get_name_size(buf.pread_with::<u8>(4, LE).ok()?, decrypt_name_table)
+            .ok()?;
+    let dot = buf.get(5 + name_size.checked_sub(4)?)?;
+    Some(!dot ^ b'.')
+}
+
+fn decrypt_file_name(buf: &[u8], name_xor_key: u8) -> PathBuf {
+    let result: Vec<u8> = buf.iter().map(|b| !b ^ name_xor_key).collect();
     PathBuf::from(SHIFT_JIS.decode(&result).0.to_string().replace("\\", "/"))
 }
@@ -20,6 +20,49 @@ pub fn zlib_decompress(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
     Ok(ret)
 }
 
+/// Adler-32 checksum stored at the end of zlib streams
+pub fn adler32(buf: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    // Largest amount of bytes which can be summed without overflowing
+    const NMAX: usize = 5552;
+
+    let (mut a, mut b) = (1_u32, 0_u32);
+    buf.chunks(NMAX).for_each(|chunk| {
+        chunk.iter().for_each(|byte| {
+            a += *byte as u32;
+            b += a;
+        });
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    });
+    b << 16 | a
+}
+
+/// Null terminated SHIFT_JIS strings containing non ASCII characters, one
+/// per line prefixed with their offset
+pub fn find_sjis_strings(buf: &[u8]) -> String {
+    use encoding_rs::SHIFT_JIS;
+
+    let mut text = String::new();
+    let mut start = 0;
+    for (end, b) in buf.iter().enumerate() {
+        if *b != 0 {
+            continue;
+        }
+        if end > start {
+            let (string, _, had_errors) = SHIFT_JIS.decode(&buf[start..end]);
+            if !had_errors
+                && !string.chars().any(char::is_control)
+                && !string.chars().all(|c| c.is_ascii())
+            {
+                text.push_str(&format!("{:08X}\t{}\n", start, string));
+            }
+        }
+        start = end + 1;
+    }
+    text
+}
+
 pub fn md5(buf: &[u8]) -> [u8; 16] {
     md5::compute(&buf, [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476])
 }
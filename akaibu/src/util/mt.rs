@@ -96,9 +96,11 @@ impl MT19937 {
         self.mt[0] = s;
         self.mti = 1;
         while self.mti < N {
-            self.mt[self.mti] = 1812433253u32.wrapping_mul(
-                self.mt[self.mti - 1] ^ (self.mt[self.mti - 1] >> 30),
-            ) + self.mti as u32;
+            self.mt[self.mti] = 1812433253u32
+                .wrapping_mul(
+                    self.mt[self.mti - 1] ^ (self.mt[self.mti - 1] >> 30),
+                )
+                .wrapping_add(self.mti as u32);
             /* See Knuth TAOCP Vol2. 3rd Ed. P.106 for multiplier. */
             /* In the previous versions, MSBs of the seed affect   */
             /* only MSBs of the array self.mt[].                        */
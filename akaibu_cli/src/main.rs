@@ -10,8 +10,9 @@
 use akaibu::{
     archive::FileEntry,
     magic::Archive,
+    options::Options,
     resource::{ResourceMagic, ResourceScheme, ResourceType},
-    scheme::Scheme,
+    scheme::{acv1, buriko, malie, siglus, Scheme},
 };
 use anyhow::Context;
 use colored::*;
@@ -40,23 +41,79 @@ struct Opt {
     /// Convert resource files to commonly used formats only one try of resource can converted at the time
     #[structopt(short, long)]
     convert: bool,
+
+    /// ACV1: Extract entries without known file name under their hash
+    #[structopt(long)]
+    acv1_extract_unnamed: bool,
+
+    /// ACV1: Additional file name lists with one name per line
+    #[structopt(long, parse(from_os_str))]
+    acv1_names: Vec<PathBuf>,
+
+    /// ACV1: Guess unknown file names from patterns e.g. bg_%03d.png
+    #[structopt(long)]
+    acv1_guess: Vec<String>,
+
+    /// ACV1: Print file name coverage report instead of extracting
+    #[structopt(long)]
+    acv1_report: bool,
+
+    /// ACV1: Write text dump of each extracted script next to it
+    #[structopt(long)]
+    acv1_dump_text: bool,
+
+    /// Siglus: Game specific key (16 bytes in hex) for encrypted Scene.pck and Gameexe.dat
+    #[structopt(long)]
+    siglus_key: Option<String>,
+
+    /// BURIKO: Keep DSC compressed entries as they are stored in archive
+    #[structopt(long)]
+    buriko_raw: bool,
+
+    /// Malie: Additional key tables in JSON format
+    #[structopt(long, parse(from_os_str))]
+    malie_keys: Vec<PathBuf>,
 }
 
 fn main() {
     env_logger::init();
     let opt = Opt::from_args();
 
+    let options = match get_options(&opt) {
+        Ok(options) => options,
+        Err(err) => {
+            log::error!("Invalid options: {}", err);
+            return;
+        }
+    };
+
     match if opt.convert {
-        convert_resource(&opt)
+        convert_resource(&opt, &options)
     } else {
-        extract_archive(&opt)
+        extract_archive(&opt, &options)
     } {
         Ok(_) => (),
         Err(err) => log::error!("Error while extracting: {}", err),
     }
 }
 
-fn convert_resource(opt: &Opt) -> anyhow::Result<()> {
+fn get_options(opt: &Opt) -> anyhow::Result<Options> {
+    Ok(Options {
+        acv1: acv1::Acv1Options {
+            extract_unnamed: opt.acv1_extract_unnamed,
+            name_lists: opt.acv1_names.clone(),
+            name_patterns: opt.acv1_guess.clone(),
+            dump_script_text: opt.acv1_dump_text,
+        },
+        siglus: siglus::SiglusOptions::new(opt.siglus_key.as_deref())?,
+        buriko: buriko::BurikoOptions {
+            keep_raw: opt.buriko_raw,
+        },
+        malie: malie::MalieOptions::from_key_tables(&opt.malie_keys)?,
+    })
+}
+
+fn convert_resource(opt: &Opt, options: &Options) -> anyhow::Result<()> {
     let not_universal = opt.files.iter().find(|f| {
         let mut magic = vec![0; 16];
         File::open(&f)
@@ -105,7 +162,7 @@ fn convert_resource(opt: &Opt) -> anyhow::Result<()> {
         .filter(|file| file.is_file())
         .try_for_each(|file| {
             log::debug!("Converting: {:?}", file);
-            match scheme.convert(&file) {
+            match scheme.convert(&file, options) {
                 Ok(resource) => write_resource(resource, file),
                 Err(err) => {
                     log::error!("Error while converting: {:?} {}", file, err);
@@ -115,7 +172,7 @@ fn convert_resource(opt: &Opt) -> anyhow::Result<()> {
         })
 }
 
-fn extract_archive(opt: &Opt) -> anyhow::Result<()> {
+fn extract_archive(opt: &Opt, options: &Options) -> anyhow::Result<()> {
     opt.files
         .iter()
         .filter(|file| file.is_file())
@@ -125,6 +182,26 @@ fn extract_archive(opt: &Opt) -> anyhow::Result<()> {
 
             let archive_magic = Archive::parse(&magic);
             log::debug!("Archive: {:?}", archive_magic);
+            if opt.acv1_report {
+                if let Archive::ACV1 = archive_magic {
+                    match acv1::coverage_report(&file, &options.acv1) {
+                        Ok(coverage) => {
+                            println!("{:?}: {}", file, coverage);
+                            coverage
+                                .guessed
+                                .iter()
+                                .for_each(|name| println!("{}", name));
+                        }
+                        Err(err) => log::error!("{:?}: {}", file, err),
+                    }
+                } else {
+                    log::error!(
+                        "{:?}: Coverage report is available only for ACV1 archives",
+                        file
+                    );
+                }
+                return Ok(());
+            }
             let schemes = if let Archive::NotRecognized = archive_magic {
                 println!(
                     "{}",
@@ -145,7 +222,7 @@ fn extract_archive(opt: &Opt) -> anyhow::Result<()> {
             };
             log::debug!("Scheme {:?}", scheme);
 
-            let (archive, dir) = match scheme.extract(&file) {
+            let (archive, dir) = match scheme.extract(&file, options) {
                 Ok(archive) => archive,
                 Err(err) => {
                     log::error!("{:?}: {}", file, err);
@@ -266,6 +343,34 @@ fn write_resource(
             File::create(new_file_name)?.write_all(s.as_bytes())?;
             Ok(())
         }
+        ResourceType::Script { data, text } => {
+            let mut new_file_name = file_name.clone();
+            new_file_name.set_extension("bin");
+            File::create(&new_file_name)?.write_all(&data)?;
+            new_file_name.set_extension("txt");
+            File::create(new_file_name)?.write_all(text.as_bytes())?;
+            Ok(())
+        }
+        ResourceType::Audio {
+            codec,
+            sample_rate,
+            channels,
+            duration,
+            data,
+        } => {
+            log::info!(
+                "{:?}: {} audio {}Hz {} channels {:.2}s",
+                file_name,
+                codec,
+                sample_rate,
+                channels,
+                duration.as_secs_f64()
+            );
+            let mut new_file_name = file_name.clone();
+            new_file_name.set_extension(codec.extension());
+            File::create(new_file_name)?.write_all(&data)?;
+            Ok(())
+        }
         ResourceType::Other => Ok(()),
         ResourceType::SpriteSheet { mut sprites } => {
             if sprites.len() == 1 {
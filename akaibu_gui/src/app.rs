@@ -6,13 +6,14 @@ use crate::{
     },
     update, Opt,
 };
-use akaibu::{magic, resource::ResourceMagic};
+use akaibu::{magic, options::Options, resource::ResourceMagic};
 use iced::{executor, Application, Command};
-use std::{fs::File, io::Read};
+use std::{fs::File, io::Read, sync::Arc};
 use structopt::StructOpt;
 
 pub(crate) struct App {
     pub(crate) opt: Opt,
+    pub(crate) options: Arc<Options>,
     pub(crate) content: Content,
 }
 
@@ -23,6 +24,7 @@ impl Application for App {
 
     fn new(_flags: Self::Flags) -> (Self, Command<Message>) {
         let opt = Opt::from_args();
+        let options = Arc::new(opt.get_options().expect("Invalid options"));
 
         let mut magic = vec![0; 32];
         File::open(&opt.file)
@@ -40,6 +42,7 @@ impl Application for App {
                 return (
                 Self {
                     opt,
+                    options,
                     content: Content::SchemeView(SchemeContent::new(
                         magic::Archive::get_all_schemes(),
                         "Archive type could not be guessed. Please enter scheme manually:"
@@ -55,11 +58,12 @@ impl Application for App {
                         .get_schemes()
                         .get(0)
                         .expect("Expected universal scheme")
-                        .convert(&file_name)
+                        .convert(&file_name, &options)
                         .expect("Could not convert resource");
                     return (
                         Self {
                             opt,
+                            options,
                             content: Content::ResourceView(
                                 ResourceContent::new(resource, file_name),
                             ),
@@ -70,6 +74,7 @@ impl Application for App {
                     return (
                         Self {
                             opt,
+                            options,
                             content: Content::ResourceSchemeView(
                                 ResourceSchemeContent::new(
                                     resource.get_schemes(),
@@ -88,11 +93,13 @@ impl Application for App {
 
         if archive.is_universal() {
             let scheme = schemes.get(0).expect("Expected universal scheme");
-            let (archive, dir) =
-                scheme.extract(&opt.file).expect("Could not extract");
+            let (archive, dir) = scheme
+                .extract(&opt.file, &options)
+                .expect("Could not extract");
             (
                 Self {
                     opt,
+                    options,
                     content: Content::ArchiveView(Box::new(
                         ArchiveContent::new(archive, dir),
                     )),
@@ -103,6 +110,7 @@ impl Application for App {
             (
                 Self {
                     opt,
+                    options,
                     content: Content::SchemeView(SchemeContent::new(
                         schemes,
                         "Select extract scheme:".to_string(),
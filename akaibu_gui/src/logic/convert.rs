@@ -1,7 +1,7 @@
 use crate::ui::resource::ConvertFormat;
 use akaibu::{
-    archive::Archive, archive::FileEntry, resource::ResourceMagic,
-    resource::ResourceType,
+    archive::Archive, archive::FileEntry, options::Options,
+    resource::ResourceMagic, resource::ResourceType,
 };
 use anyhow::Context;
 use image::ImageFormat;
@@ -11,9 +11,13 @@ pub async fn convert_resource(
     archive: Arc<Box<dyn Archive>>,
     entry: FileEntry,
     file_path: PathBuf,
+    options: Arc<Options>,
 ) -> anyhow::Result<PathBuf> {
     let contents = archive.extract(&entry)?;
-    let resource_magic = ResourceMagic::parse_magic(&contents);
+    let mut resource_magic = ResourceMagic::parse_magic(&contents);
+    if let ResourceMagic::Unrecognized = resource_magic {
+        resource_magic = ResourceMagic::parse_file_extension(&entry.full_path);
+    }
     log::info!("Converting resource {:?}", resource_magic);
     let mut converted_path = file_path;
     converted_path.set_file_name(&entry.file_name);
@@ -22,7 +26,7 @@ pub async fn convert_resource(
             .get_schemes()
             .get(0)
             .context("Expected universal scheme")?
-            .convert_from_bytes(&converted_path, contents.to_vec())?,
+            .convert_from_bytes(&converted_path, contents.to_vec(), &options)?,
         &entry,
         &converted_path,
     )?;
@@ -34,9 +38,13 @@ pub fn convert_resource_blocking(
     archive: &Box<dyn Archive>,
     entry: &FileEntry,
     file_path: &PathBuf,
+    options: &Options,
 ) -> anyhow::Result<PathBuf> {
     let contents = archive.extract(&entry)?;
-    let resource_magic = ResourceMagic::parse_magic(&contents);
+    let mut resource_magic = ResourceMagic::parse_magic(&contents);
+    if let ResourceMagic::Unrecognized = resource_magic {
+        resource_magic = ResourceMagic::parse_file_extension(&entry.full_path);
+    }
     log::info!("Converting resource {:?}", resource_magic);
     let mut converted_path = file_path.clone();
     converted_path.set_file_name(&entry.file_name);
@@ -45,7 +53,7 @@ pub fn convert_resource_blocking(
             .get_schemes()
             .get(0)
             .context("Expected universal scheme")?
-            .convert_from_bytes(&converted_path, contents.to_vec())?,
+            .convert_from_bytes(&converted_path, contents.to_vec(), options)?,
         &entry,
         file_path,
     )?;
@@ -94,6 +102,20 @@ fn write_resource(
             File::create(new_file_name)?.write_all(s.as_bytes())?;
             Ok(())
         }
+        ResourceType::Script { data, text } => {
+            let mut new_file_name = file_name.clone();
+            new_file_name.set_extension("bin");
+            File::create(&new_file_name)?.write_all(&data)?;
+            new_file_name.set_extension("txt");
+            File::create(new_file_name)?.write_all(text.as_bytes())?;
+            Ok(())
+        }
+        ResourceType::Audio { codec, data, .. } => {
+            let mut new_file_name = file_name.clone();
+            new_file_name.set_extension(codec.extension());
+            File::create(new_file_name)?.write_all(&data)?;
+            Ok(())
+        }
         ResourceType::Other => Err(akaibu::error::AkaibuError::Custom(
             format!("Convert not available for: {}", entry.file_name),
         )
@@ -173,6 +195,22 @@ fn write_resource_entry(
             File::create(new_file_name)?.write_all(s.as_bytes())?;
             Ok(())
         }
+        ResourceType::Script { data, text } => {
+            let mut new_file_name = file_path.clone();
+            new_file_name.push(entry.full_path.clone());
+            new_file_name.set_extension("bin");
+            File::create(&new_file_name)?.write_all(&data)?;
+            new_file_name.set_extension("txt");
+            File::create(new_file_name)?.write_all(text.as_bytes())?;
+            Ok(())
+        }
+        ResourceType::Audio { codec, data, .. } => {
+            let mut new_file_name = file_path.clone();
+            new_file_name.push(entry.full_path.clone());
+            new_file_name.set_extension(codec.extension());
+            File::create(new_file_name)?.write_all(&data)?;
+            Ok(())
+        }
         ResourceType::Other => Err(akaibu::error::AkaibuError::Unimplemented(
             format!("Convert not available for: {}", entry.file_name),
         )
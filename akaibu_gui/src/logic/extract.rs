@@ -1,5 +1,8 @@
 use super::convert;
-use akaibu::archive::{Archive, FileEntry};
+use akaibu::{
+    archive::{Archive, FileEntry},
+    options::Options,
+};
 use anyhow::Context;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::{fs::File, io::Write, path::PathBuf, sync::Arc};
@@ -63,6 +66,7 @@ pub async fn extract_all_with_convert(
     archive: Arc<Box<dyn Archive>>,
     files: Vec<FileEntry>,
     file_path: PathBuf,
+    options: Arc<Options>,
 ) -> anyhow::Result<PathBuf> {
     let mut extract_path = file_path
         .file_name()
@@ -82,6 +86,7 @@ pub async fn extract_all_with_convert(
                 &archive,
                 &entry,
                 &output_path,
+                &options,
             ) {
                 Ok(_) => Ok(()),
                 Err(_) => {
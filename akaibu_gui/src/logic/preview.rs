@@ -1,22 +1,26 @@
 use std::sync::Arc;
 
 use akaibu::{
-    archive::Archive, archive::FileEntry, resource::ResourceMagic,
-    resource::ResourceType,
+    archive::Archive, archive::FileEntry, options::Options,
+    resource::ResourceMagic, resource::ResourceType,
 };
 use anyhow::Context;
 
 pub async fn get_resource_type(
     archive: Arc<Box<dyn Archive>>,
     entry: FileEntry,
+    options: Arc<Options>,
 ) -> anyhow::Result<ResourceType> {
     let contents = archive.extract(&entry)?;
-    let resource_magic = ResourceMagic::parse_magic(&contents);
+    let mut resource_magic = ResourceMagic::parse_magic(&contents);
+    if let ResourceMagic::Unrecognized = resource_magic {
+        resource_magic = ResourceMagic::parse_file_extension(&entry.full_path);
+    }
     let resource = resource_magic
         .get_schemes()
         .get(0)
         .context("Expected universal scheme")?
-        .convert_from_bytes(&entry.full_path, contents.to_vec())?;
+        .convert_from_bytes(&entry.full_path, contents.to_vec(), &options)?;
 
     Ok(match resource {
         ResourceType::Other => PreviewableResourceMagic::parse(&contents)?,
@@ -30,7 +34,7 @@ enum PreviewableResourceMagic {
     JPG,
     BMP,
     ICO,
-    RIFF,
+    WEBP,
     Unrecognized,
 }
 
@@ -42,14 +46,15 @@ impl PreviewableResourceMagic {
             [255, 216, 255, ..] => Self::JPG,
             [66, 77, ..] => Self::BMP,
             [0, 0, 1, 0, ..] => Self::ICO,
-            [82, 73, 70, 70, ..] => Self::RIFF,
+            // RIFF....WEBP, RIFF/WAVE is handled as audio resource
+            [82, 73, 70, 70, _, _, _, _, 87, 69, 66, 80, ..] => Self::WEBP,
             _ => Self::Unrecognized,
         }
     }
     fn parse(buf: &[u8]) -> anyhow::Result<ResourceType> {
         use self::PreviewableResourceMagic::*;
         Ok(match Self::parse_magic(buf) {
-            PNG | JPG | BMP | ICO | RIFF => ResourceType::RgbaImage {
+            PNG | JPG | BMP | ICO | WEBP => ResourceType::RgbaImage {
                 image: image::load_from_memory(buf)?.to_rgba8(),
             },
             Unrecognized => ResourceType::Other,
@@ -14,6 +14,10 @@ mod style;
 mod ui;
 mod update;
 
+use akaibu::{
+    options::Options,
+    scheme::{acv1, buriko, malie, siglus},
+};
 use app::App;
 use iced::{window, Application, Settings};
 use std::path::PathBuf;
@@ -25,6 +29,52 @@ pub(crate) struct Opt {
     /// File to process
     #[structopt(required = true, name = "ARCHIVE", parse(from_os_str))]
     pub(crate) file: PathBuf,
+
+    /// ACV1: Extract entries without known file name under their hash
+    #[structopt(long)]
+    pub(crate) acv1_extract_unnamed: bool,
+
+    /// ACV1: Additional file name lists with one name per line
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) acv1_names: Vec<PathBuf>,
+
+    /// ACV1: Guess unknown file names from patterns e.g. bg_%03d.png
+    #[structopt(long)]
+    pub(crate) acv1_guess: Vec<String>,
+
+    /// ACV1: Write text dump of each extracted script next to it
+    #[structopt(long)]
+    pub(crate) acv1_dump_text: bool,
+
+    /// Siglus: Game specific key (16 bytes in hex) for encrypted Scene.pck and Gameexe.dat
+    #[structopt(long)]
+    pub(crate) siglus_key: Option<String>,
+
+    /// BURIKO: Keep DSC compressed entries as they are stored in archive
+    #[structopt(long)]
+    pub(crate) buriko_raw: bool,
+
+    /// Malie: Additional key tables in JSON format
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) malie_keys: Vec<PathBuf>,
+}
+
+impl Opt {
+    pub(crate) fn get_options(&self) -> anyhow::Result<Options> {
+        Ok(Options {
+            acv1: acv1::Acv1Options {
+                extract_unnamed: self.acv1_extract_unnamed,
+                name_lists: self.acv1_names.clone(),
+                name_patterns: self.acv1_guess.clone(),
+                dump_script_text: self.acv1_dump_text,
+            },
+            siglus: siglus::SiglusOptions::new(self.siglus_key.as_deref())?,
+            buriko: buriko::BurikoOptions {
+                keep_raw: self.buriko_raw,
+            },
+            malie: malie::MalieOptions::from_key_tables(&self.malie_keys)?,
+        })
+    }
 }
 
 fn main() -> Result<(), iced::Error> {
@@ -80,7 +80,8 @@ impl Preview {
                 .width(Length::Fill)
                 .height(Length::Fill)
             }
-            resource::ResourceType::Text(text) => Container::new(
+            resource::ResourceType::Text(text)
+            | resource::ResourceType::Script { text, .. } => Container::new(
                 Text::new(text)
                     .width(Length::Fill)
                     .height(Length::Fill)
@@ -89,6 +90,28 @@ impl Preview {
             )
             .width(Length::Fill)
             .height(Length::Fill),
+            resource::ResourceType::Audio {
+                codec,
+                sample_rate,
+                channels,
+                duration,
+                ..
+            } => Container::new(
+                Text::new(format!(
+                    "{} audio {}Hz {} channels {}:{:02}",
+                    codec,
+                    sample_rate,
+                    channels,
+                    duration.as_secs() / 60,
+                    duration.as_secs() % 60
+                ))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .vertical_alignment(VerticalAlignment::Center)
+                .horizontal_alignment(HorizontalAlignment::Center),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill),
             resource::ResourceType::Other => Container::new(
                 Text::new("No preview available...")
                     .width(Length::Fill)
@@ -118,12 +118,36 @@ impl ResourceContent {
                 .width(Length::Fill)
                 .height(Length::Fill)
             }
-            ResourceType::Text(text) => Container::new(
-                Text::new(text)
-                    .width(Length::Fill)
-                    .height(Length::Fill)
-                    .vertical_alignment(VerticalAlignment::Center)
-                    .horizontal_alignment(HorizontalAlignment::Center),
+            ResourceType::Text(text) | ResourceType::Script { text, .. } => {
+                Container::new(
+                    Text::new(text)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .vertical_alignment(VerticalAlignment::Center)
+                        .horizontal_alignment(HorizontalAlignment::Center),
+                )
+                .width(Length::Fill)
+                .height(Length::Fill)
+            }
+            ResourceType::Audio {
+                codec,
+                sample_rate,
+                channels,
+                duration,
+                ..
+            } => Container::new(
+                Text::new(format!(
+                    "{} audio {}Hz {} channels {}:{:02}",
+                    codec,
+                    sample_rate,
+                    channels,
+                    duration.as_secs() / 60,
+                    duration.as_secs() % 60
+                ))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .vertical_alignment(VerticalAlignment::Center)
+                .horizontal_alignment(HorizontalAlignment::Center),
             )
             .width(Length::Fill)
             .height(Length::Fill),
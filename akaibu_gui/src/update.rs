@@ -37,6 +37,7 @@ pub(crate) fn handle_message(
                         content.archive.clone(),
                         file_entry,
                         app.opt.file.clone(),
+                        app.options.clone(),
                     ),
                     |result| match result {
                         Ok(path) => Message::SetStatus(Status::Success(
@@ -76,6 +77,7 @@ pub(crate) fn handle_message(
                     preview::get_resource_type(
                         content.archive.clone(),
                         file_entry.clone(),
+                        app.options.clone(),
                     ),
                     move |result| match result {
                         Ok(resource) => Message::OpenPreview(
@@ -108,6 +110,7 @@ pub(crate) fn handle_message(
                                 .cloned()
                                 .collect(),
                             app.opt.file.clone(),
+                            app.options.clone(),
                         ),
                         |result| match result {
                             Ok(path) => Message::SetStatus(Status::Success(
@@ -150,13 +153,14 @@ pub(crate) fn handle_message(
         }
         Message::MoveScene(scene) => match scene {
             Scene::ArchiveView(scheme) => {
-                let (archive, dir) = scheme.extract(&app.opt.file)?;
+                let (archive, dir) =
+                    scheme.extract(&app.opt.file, &app.options)?;
                 app.content = Content::ArchiveView(Box::new(
                     ArchiveContent::new(archive, dir),
                 ));
             }
             Scene::ResourceView(scheme, file_path) => {
-                let resource = scheme.convert(&app.opt.file)?;
+                let resource = scheme.convert(&app.opt.file, &app.options)?;
                 app.content = Content::ResourceView(ResourceContent::new(
                     resource, file_path,
                 ));